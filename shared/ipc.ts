@@ -1,4 +1,4 @@
-import type { DeviceType, AvailableEngine, ModelGroup, WeightFile, GenerateImageParams, GeneratedImageInfo, MirrorSource, SDCppRelease, SDCppDownloadProgress, MirrorTestResult, SDCppReleaseAsset, HfMirrorId, ModelDownloadProgress, DownloadConfig } from './types.js'
+import type { DeviceType, AvailableEngine, ModelGroup, WeightFile, GenerateImageParams, GeneratedImageInfo, MirrorSource, SDCppRelease, SDCppDownloadProgress, MirrorTestResult, SDCppReleaseAsset, HfMirrorId, ModelDownloadProgress, DownloadConfig, ModelConvertRequest, ModelConvertProgress, OutpaintParams, EmbeddingInfo, VideoGenerateParams, FfmpegOutcome, VideoExportRequest, VideoPostprocessRequest, VideoPostprocessProgress, FfmpegCandidate, FfmpegInfo, VideoSlideshowRequest, VideoTrimRequest, VideoConcatRequest, VideoExtractFrameRequest, GalleryRoot, GallerySearchRequest, GallerySearchResult, GalleryAnnotationFilter, GalleryAnnotationUpdate, GalleryTagCount, CollectionSummary, CollectionContents, CollectionItemsRequest, TrashEntry, TrashPolicy, GalleryChangedEvent, WeightsChangedEvent } from './types.js'
 
 // IPC channel allowlists
 export const IPC_INVOKE_CHANNELS = [
@@ -6,19 +6,46 @@ export const IPC_INVOKE_CHANNELS = [
   'app:get-version',
   'system:get-available-engines',
   'devtools:toggle',
+  'collections:add-items',
+  'collections:create',
+  'collections:delete',
+  'collections:export',
+  'collections:get-items',
+  'collections:list',
+  'collections:remove-items',
+  'collections:rename',
   'dialog:open-image',
   'edit-image:read-image-base64',
   'edit-image:select-file',
+  'embeddings:get-folder',
+  'embeddings:list',
+  'embeddings:set-folder',
+  'ffmpeg:cancel-download',
+  'ffmpeg:detect',
+  'ffmpeg:download',
+  'ffmpeg:get-info',
+  'ffmpeg:get-path',
+  'ffmpeg:import',
+  'ffmpeg:set-path',
   'generate-video:cancel',
   'generate-video:start',
+  'gallery:search',
   'generate:cancel',
+  'generate:outpaint',
   'generate:start',
+  'generated-images:annotate',
   'generated-images:batch-download',
   'generated-images:delete',
   'generated-images:download',
+  'generated-images:get-outputs-folder',
   'generated-images:get-preview',
+  'generated-images:get-roots',
   'generated-images:get-video-data',
   'generated-images:list',
+  'generated-images:list-tags',
+  'generated-images:regenerate',
+  'generated-images:set-outputs-folder',
+  'generated-images:set-roots',
   'model-groups:build-and-export',
   'model-groups:create',
   'model-groups:delete',
@@ -31,6 +58,8 @@ export const IPC_INVOKE_CHANNELS = [
   'models:check-files',
   'models:delete-file',
   'models:clear-verified',
+  'models:convert',
+  'models:cancel-convert',
   'models:download-group-files',
   'models:get-hf-mirror',
   'models:set-hf-mirror',
@@ -57,6 +86,19 @@ export const IPC_INVOKE_CHANNELS = [
   'sdcpp:remove-mirror',
   'sdcpp:set-device',
   'sdcpp:test-mirrors',
+  'trash:empty',
+  'trash:get-policy',
+  'trash:list',
+  'trash:purge',
+  'trash:restore',
+  'trash:set-policy',
+  'video:cancel-postprocess',
+  'video:concat',
+  'video:export',
+  'video:extract-frame',
+  'video:postprocess',
+  'video:slideshow',
+  'video:trim',
   'weights:check-folder',
   'weights:delete-file',
   'weights:download-file',
@@ -77,6 +119,11 @@ export const IPC_EVENT_CHANNELS = [
   'model-groups:import-progress',
   'sdcpp:download-progress',
   'models:download-progress',
+  'models:convert-progress',
+  'ffmpeg:download-progress',
+  'video:postprocess-progress',
+  'gallery:changed',
+  'weights:changed',
 ] as const
 
 export type IpcInvokeChannel = typeof IPC_INVOKE_CHANNELS[number]
@@ -110,6 +157,14 @@ export interface IPCRequestMap {
   'sdcpp:test-mirrors': { request: void; response: MirrorTestResult[] }
   'sdcpp:auto-select-mirror': { request: void; response: MirrorSource }
 
+  'ffmpeg:detect': { request: void; response: FfmpegCandidate[] }
+  'ffmpeg:get-info': { request: string | void; response: FfmpegInfo }
+  'ffmpeg:get-path': { request: void; response: string | null }
+  'ffmpeg:set-path': { request: string; response: boolean }
+  'ffmpeg:download': { request: string | void; response: { success: boolean; path?: string; version?: string | null; error?: string } }
+  'ffmpeg:cancel-download': { request: void; response: boolean }
+  'ffmpeg:import': { request: string; response: { success: boolean; path?: string; version?: string | null } }
+
   'models:get-hf-mirror': { request: void; response: HfMirrorId }
   'models:set-hf-mirror': { request: HfMirrorId; response: boolean }
   'models:get-download-config': { request: void; response: DownloadConfig }
@@ -120,6 +175,12 @@ export interface IPCRequestMap {
   'models:verify-file': { request: { groupId: string; filePath: string }; response: { success: boolean; expectedSize: number; actualSize: number } }
   'models:delete-file': { request: { groupId: string; filePath: string }; response: boolean }
   'models:clear-verified': { request: { groupId: string }; response: boolean }
+  'models:convert': { request: ModelConvertRequest; response: { success: boolean; outputPath?: string; size?: number; duration?: number; updatedSlots?: string[]; error?: string } }
+  'models:cancel-convert': { request: void; response: boolean }
+
+  'embeddings:get-folder': { request: void; response: string }
+  'embeddings:set-folder': { request: string; response: boolean }
+  'embeddings:list': { request: void; response: EmbeddingInfo[] }
 
   'model-groups:list': { request: void; response: ModelGroup[] }
   'model-groups:create': { request: Omit<ModelGroup, 'id' | 'createdAt' | 'updatedAt'>; response: ModelGroup }
@@ -130,18 +191,52 @@ export interface IPCRequestMap {
   'model-groups:import': { request: { folderPath: string; targetFolder: string }; response: { success: boolean; message?: string; group?: ModelGroup; error?: string } }
   'model-groups:build-and-export': { request: Omit<ModelGroup, 'id' | 'createdAt' | 'updatedAt'>; response: { success: boolean; message?: string; exportPath?: string; error?: string } }
 
-  'generate:start': { request: GenerateImageParams; response: { success: boolean; image?: string; imagePath?: string; duration?: number; error?: string } }
+  'generate:start': { request: GenerateImageParams; response: { success: boolean; image?: string; imagePath?: string; tilePreviewPath?: string | null; duration?: number; dryRun?: boolean; args?: string[]; commandLine?: string; effectiveParams?: GenerateImageParams; error?: string } }
   'generate:cancel': { request: void; response: { success: boolean; message?: string; error?: string } }
+  'generate:outpaint': { request: OutpaintParams; response: { success: boolean; image?: string; imagePath?: string; width?: number; height?: number; duration?: number; error?: string } }
 
-  'generate-video:start': { request: GenerateImageParams & { frames?: number; fps?: number; mode?: string }; response: { success: boolean; video?: string; videoPath?: string; duration?: number; frames?: string[]; error?: string } }
+  'generate-video:start': { request: VideoGenerateParams; response: { success: boolean; video?: string; videoPath?: string; encodeResult?: FfmpegOutcome | null; duration?: number; frames?: string[]; dryRun?: boolean; args?: string[]; commandLine?: string; effectiveParams?: GenerateImageParams; error?: string } }
   'generate-video:cancel': { request: void; response: boolean }
 
+  'video:export': { request: VideoExportRequest; response: { success: boolean; outputPath?: string; size?: number } }
+  'video:postprocess': { request: VideoPostprocessRequest; response: { success: boolean; video?: string; videoPath?: string; duration?: number; encodeResult?: FfmpegOutcome; error?: string } }
+  'video:cancel-postprocess': { request: void; response: boolean }
+  'video:slideshow': { request: VideoSlideshowRequest; response: { success: boolean; video?: string; videoPath?: string; duration?: number; encodeResult?: FfmpegOutcome; error?: string } }
+  'video:trim': { request: VideoTrimRequest; response: { success: boolean; video?: string; videoPath?: string; duration?: number; encodeResult?: FfmpegOutcome; error?: string } }
+  'video:concat': { request: VideoConcatRequest; response: { success: boolean; video?: string; videoPath?: string; duration?: number; encodeResult?: FfmpegOutcome; error?: string } }
+  'video:extract-frame': { request: VideoExtractFrameRequest; response: { success: boolean; image?: string; imagePath?: string } }
+
+  // 可通过 ipcInvokeWithPayload 传入 { value: GalleryAnnotationFilter } 进行筛选
   'generated-images:list': { request: void; response: GeneratedImageInfo[] }
+  'generated-images:annotate': { request: GalleryAnnotationUpdate; response: GeneratedImageInfo[] }
+  'generated-images:list-tags': { request: void; response: GalleryTagCount[] }
+  'generated-images:get-outputs-folder': { request: void; response: string }
+  'generated-images:set-outputs-folder': { request: string; response: boolean }
+  'generated-images:get-roots': { request: void; response: GalleryRoot[] }
+  'generated-images:set-roots': { request: [GalleryRoot[]]; response: boolean }
+  'gallery:search': { request: GallerySearchRequest; response: GallerySearchResult }
   'generated-images:download': { request: string; response: boolean }
   'generated-images:delete': { request: string; response: boolean }
   'generated-images:get-preview': { request: string; response: string }
   'generated-images:get-video-data': { request: string; response: { data: number[]; mimeType: string } }
   'generated-images:batch-download': { request: [string[]]; response: { success: boolean; zipPath?: string; size?: number; canceled?: boolean } }
+  'generated-images:regenerate': { request: { path: string; overrides?: Partial<GenerateImageParams> & Record<string, unknown> }; response: { success: boolean; image?: string; imagePath?: string; video?: string; videoPath?: string; duration?: number; warnings?: string[]; error?: string } }
+
+  'collections:list': { request: void; response: CollectionSummary[] }
+  'collections:create': { request: string; response: CollectionSummary }
+  'collections:rename': { request: { id: string; name: string }; response: CollectionSummary }
+  'collections:delete': { request: string; response: boolean }
+  'collections:add-items': { request: CollectionItemsRequest; response: CollectionSummary }
+  'collections:remove-items': { request: CollectionItemsRequest; response: CollectionSummary }
+  'collections:get-items': { request: string; response: CollectionContents }
+  'collections:export': { request: string; response: { success: boolean; zipPath?: string; size?: number; missing?: string[]; canceled?: boolean } }
+
+  'trash:list': { request: void; response: TrashEntry[] }
+  'trash:restore': { request: string; response: TrashEntry }
+  'trash:purge': { request: [string[]]; response: number }
+  'trash:empty': { request: void; response: number }
+  'trash:get-policy': { request: void; response: TrashPolicy }
+  'trash:set-policy': { request: TrashPolicy; response: TrashPolicy }
 
   'dialog:open-image': { request: void; response: string | null }
   'edit-image:select-file': { request: void; response: string | null }
@@ -168,6 +263,11 @@ export interface IPCEventMap {
   'generate-video:cli-output': { type: 'stdout' | 'stderr'; text: string }
   'sdcpp:download-progress': SDCppDownloadProgress
   'models:download-progress': ModelDownloadProgress
+  'models:convert-progress': ModelConvertProgress
+  'ffmpeg:download-progress': SDCppDownloadProgress
+  'video:postprocess-progress': VideoPostprocessProgress
+  'gallery:changed': GalleryChangedEvent
+  'weights:changed': WeightsChangedEvent
 }
 
 export type IpcInvokeArgs<C extends IpcInvokeChannel> = IPCRequestMap[C]['request'] extends void
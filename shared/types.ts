@@ -31,7 +31,7 @@ export type TaskType = 'generate' | 'edit' | 'video' | 'upscale'
 /**
  * 生成类型
  */
-export type GenerationType = 'generate' | 'edit' | 'video'
+export type GenerationType = 'generate' | 'edit' | 'video' | 'outpaint' | 'tile-preview' | 'postprocess' | 'slideshow' | 'trim' | 'concat' | 'frame'
 
 /**
  * 媒体类型
@@ -55,6 +55,17 @@ export interface WeightFile {
   modified: number
 }
 
+/**
+ * 权重目录变化事件（由后端轮询检测，已合并防抖）
+ */
+export interface WeightsChangedEvent {
+  folder: string
+  added: string[]
+  removed: string[]
+  modified: string[]
+  files: WeightFile[] // 新增与修改文件的最新信息
+}
+
 // ─── 模型组 ─────────────────────────────────────────────────────────────
 
 /**
@@ -73,6 +84,9 @@ export interface ModelGroup {
   clipLModel?: string // CLIP L模型路径（图片编辑任务用，可选）
   t5xxlModel?: string // T5XXL模型路径（图片编辑任务用，可选）
   clipVisionModel?: string // CLIP Vision模型路径（视频生成 I2V/FLF2V 用，可选）
+  taesdModel?: string // TAESD 轻量解码器路径（加速预览/解码，可选）
+  llmVisionModel?: string // LLM 视觉投影模型路径（Qwen-Image-Edit 等参考图编辑用，可选）
+  photoMakerModel?: string // PhotoMaker 模型路径（身份保持生成，可选）
   hfFiles?: HfFileRef[] // HuggingFace 待下载文件列表（预定义）
   defaultSteps?: number // 推荐的默认采样步数
   defaultCfgScale?: number // 推荐的默认CFG Scale值
@@ -90,8 +104,26 @@ export interface ModelGroup {
   updatedAt: number
 }
 
+// ─── 嵌入（Textual Inversion） ──────────────────────────────────────────
+
+/**
+ * 嵌入文件信息（名称即提示词中的触发词）
+ */
+export interface EmbeddingInfo {
+  name: string
+  path: string
+  size: number
+  format: string // safetensors / pt / bin / ckpt
+  tokens?: number | null // 嵌入占用的 token 数，无法解析时为空
+}
+
 // ─── 生成参数 ───────────────────────────────────────────────────────────
 
+/**
+ * 循环填充方向：both 对应 --circular，x/y 对应 --circularx/--circulary
+ */
+export type CircularPadding = 'both' | 'x' | 'y'
+
 /**
  * 图片生成参数
  */
@@ -99,10 +131,15 @@ export interface GenerateImageParams {
   groupId?: string // 使用模型组ID
   sdModel?: string
   diffusionModel?: string // 独立扩散模型路径（如 Z-Image）
+  highNoiseSdModel?: string // 高噪声扩散模型路径（Wan2.2 视频生成）
+  clipVisionModel?: string // CLIP Vision 模型路径（视频 I2V/FLF2V）
   vaeModel?: string
   llmModel?: string
   clipLModel?: string
   t5xxlModel?: string
+  taesdModel?: string // TAESD 轻量解码器路径
+  llmVisionModel?: string // LLM 视觉投影模型路径
+  photoMakerModel?: string // PhotoMaker 模型路径
   deviceType: DeviceType | string // 支持标准设备类型或 CPU 变体（如 'cpu-avx2'）
   prompt: string
   negativePrompt?: string
@@ -117,6 +154,7 @@ export interface GenerateImageParams {
   threads?: number // 线程数，undefined 表示自动
   preview?: string // 预览方法
   previewInterval?: number // 预览间隔
+  taesdPreviewOnly?: boolean // TAESD 仅用于预览，最终图像仍使用完整 VAE 解码
   verbose?: boolean // 详细输出
   color?: boolean // 彩色日志
   offloadToCpu?: boolean // 卸载到CPU
@@ -128,6 +166,13 @@ export interface GenerateImageParams {
   vaeConvDirect?: boolean // 在VAE模型中使用ggml_conv2d_direct
   vaeTiling?: boolean // 分块处理VAE以减少内存使用
   inputImage?: string // 输入图片路径（用于图片编辑和上采样）
+  maskImage?: string // 重绘蒙版路径（白色区域重绘）
+  refImages?: string[] // 参考图路径列表（Flux Kontext / Qwen-Image-Edit 指令编辑，相对路径基于输出目录）
+  strength?: number // 重绘强度（img2img / inpaint）
+  idImages?: string[] // PhotoMaker 身份参考图路径列表（提示词中需包含触发词 img）
+  pmStyleStrength?: number // PhotoMaker 风格强度
+  circularPadding?: CircularPadding // 循环填充（无缝贴图），生成后附带 2x2 平铺预览
+  dryRun?: boolean // 仅返回合并模型组默认值后的参数与命令行，不实际生成
   mode?: string // 生成模式（如 text2video, image2video）
   initImage?: string // 初始图片路径（用于视频生成等）
   // 视频/图片生成特有参数（Wan2.2, Qwen2511等）
@@ -138,8 +183,160 @@ export interface GenerateImageParams {
   highNoiseSamplingMethod?: string
 }
 
+/**
+ * 视频编码配置（sd.cpp 输出 AVI 后由 FFmpeg 转码）
+ */
+export interface VideoEncodeProfile {
+  codec?: 'h264' | 'h265' | 'vp9' | 'av1' // 默认 h264
+  container?: 'mp4' | 'webm' // 默认 VP9 为 webm，其余为 mp4
+  crf?: number // 质量（越小越好）
+  preset?: string // x264/x265 速度预设
+  pixelFormat?: string // 像素格式，默认 yuv420p（浏览器兼容）
+  keepAvi?: boolean // 保留原始 AVI 文件（另存为 generated_<ts>_original.avi，带独立的元数据）
+}
+
+/**
+ * 视频导出请求（动图或 PNG 帧序列压缩包，输出到 outputs/exports）
+ */
+export interface VideoExportRequest {
+  path: string // 源视频路径
+  format: 'gif' | 'webp' | 'png-zip'
+  width?: number // 输出宽度，高度按比例
+  fps?: number // 输出帧率，默认与源视频一致
+  loop?: boolean // 循环播放，默认 true（PNG 帧序列忽略）
+  quality?: number // WebP 质量 0-100，默认 80
+}
+
+/**
+ * 视频裁剪请求（按时间范围，单位秒）
+ */
+export interface VideoTrimRequest {
+  path: string
+  start?: number // 默认 0
+  end?: number // 默认到视频结尾
+  encoding?: VideoEncodeProfile
+}
+
+/**
+ * 视频拼接请求：分辨率与帧率必须一致；未指定编码时尽量直接复制流
+ */
+export interface VideoConcatRequest {
+  paths: string[]
+  encoding?: VideoEncodeProfile
+}
+
+/**
+ * 视频抽帧请求：按帧序号、时间或取最后一帧，保存为 PNG 作品
+ */
+export interface VideoExtractFrameRequest {
+  path: string
+  frame?: number // 从 0 开始的帧序号
+  time?: number // 秒，未指定 frame 时使用
+  last?: boolean // 取最后一帧（用于衔接下一次图生视频）
+}
+
+/**
+ * 幻灯片 / 延时视频请求：按顺序将图库图片合成为视频
+ */
+export interface VideoSlideshowRequest {
+  images: string[] // 按播放顺序排列的图片路径
+  imageDuration?: number // 每张图片显示秒数（幻灯片模式，默认 2）
+  fps?: number // 每秒图片数（延时模式），优先于 imageDuration
+  crossfade?: number // 交叉淡化秒数，仅幻灯片模式
+  width?: number // 输出尺寸，默认取第一张图片尺寸
+  height?: number
+  encoding?: VideoEncodeProfile
+}
+
+/**
+ * 视频后处理请求（补帧 / 逐帧 ESRGAN 放大 / 重新编码），结果作为新的作品保存
+ */
+export interface VideoPostprocessRequest {
+  path: string // 源视频路径
+  targetFps?: number // 补帧目标帧率（FFmpeg minterpolate）
+  upscaleModel?: string // ESRGAN 放大模型路径（相对路径基于权重目录）
+  upscaleRepeats?: number // 放大重复次数
+  deviceType?: string // SD.cpp 设备类型，默认使用当前设备
+  threads?: number
+  encoding?: VideoEncodeProfile
+}
+
+/**
+ * 视频后处理进度
+ */
+export interface VideoPostprocessProgress {
+  stage: 'extracting' | 'upscaling' | 'encoding' | 'done'
+  current?: number | null
+  total?: number | null
+  text: string
+}
+
+/**
+ * FFmpeg 可执行文件候选项
+ */
+export interface FfmpegCandidate {
+  path: string
+  source: 'selected' | 'bundled' | 'system' // 手动选择 / 引擎目录 / 系统 PATH
+  version?: string | null
+}
+
+/**
+ * FFmpeg 版本与编码器信息
+ */
+export interface FfmpegInfo {
+  path: string
+  version?: string | null
+  encoders: Array<{ name: string; kind: 'video' | 'audio' | 'subtitle'; description: string }>
+}
+
+/**
+ * FFmpeg 执行结果
+ */
+export interface FfmpegOutcome {
+  success: boolean
+  exitCode?: number | null
+  stderr?: string // FFmpeg 标准错误输出（末尾若干行）
+  error?: string
+}
+
+/**
+ * 视频生成参数
+ */
+export interface VideoGenerateParams extends GenerateImageParams {
+  videoFrames?: number // 视频帧数（--video-frames）
+  frames?: number // 同 videoFrames（旧字段）
+  fps?: number // 帧率（--fps），同时用于 FFmpeg 转码
+  endImage?: string // 尾帧图片路径（首尾帧 FLF2V 模式，需同时提供 initImage）
+  controlVideo?: string // 控制视频：帧图片目录或视频文件（视频文件会用 FFmpeg 拆帧）
+  encoding?: VideoEncodeProfile // 转码配置
+}
+
+/**
+ * 扩图参数：在原图四周扩展画布并重绘新区域
+ */
+export interface OutpaintParams extends GenerateImageParams {
+  sourceImage: string // 原图路径（PNG）
+  extend: { left?: number; top?: number; right?: number; bottom?: number } // 各方向扩展像素
+  overlap?: number // 蒙版向原图内延伸的像素数，用于融合接缝，默认 8
+}
+
 // ─── 生成结果 ───────────────────────────────────────────────────────────
 
+/**
+ * 缩略图标准尺寸（长边像素）。通过 media://<编码后的路径>?size=256&format=webp 获取，
+ * 其他尺寸会向上取整到标准尺寸；format 默认为 jpeg，webp 为无损并保留透明度
+ */
+export type ThumbnailSize = 128 | 256 | 512
+
+/**
+ * 额外的只读图库目录（如旧的输出目录或其他工具的输出目录）
+ */
+export interface GalleryRoot {
+  path: string
+  label?: string | null // 显示名称
+  depth: number // 扫描子目录层数，0 表示只扫描该目录本身
+}
+
 /**
  * 已生成图片/视频的元数据
  */
@@ -149,6 +346,8 @@ export interface GeneratedImageInfo {
   path: string
   size: number
   modified: number
+  root?: string | null // 所属的额外图库目录，输出目录中的作品为空
+  readOnly: boolean // 额外图库目录中的作品只读，不可删除
   width?: number
   height?: number
 
@@ -199,15 +398,205 @@ export interface GeneratedImageInfo {
 
   // 元数据
   commandLine?: string
+  models?: Record<string, ModelFileInfo> | null // 按模型组字段（sdModel、vaeModel 等）记录的实际模型文件
+  engine?: EngineInfo | null
+  regeneratedFrom?: string | null // 重新生成来源（原作品路径）
+  sourceImage?: string | null // 扩图等操作的原图路径
+  sourceVideo?: string | null // 视频后处理的源视频路径
+  sourceImages?: string[] | null // 幻灯片 / 延时视频的源图片
+  sourceVideos?: string[] | null // 拼接视频的源视频
+  refImages?: string[] | null // 参考图路径
+  videoFrames?: number | null // 视频帧数
+  fps?: number | null // 视频帧率
+  videoDuration?: number | null // 视频时长（秒），由 FFmpeg 探测
+  videoCodec?: string | null // 视频编码
+  poster?: string | null // 视频封面帧路径（缓存于 .posters 目录）
+  // 整理信息（保存在 sidecar JSON 中）
+  rating?: number | null // 星级 1-5
+  favorite: boolean
+  tags: string[]
+  note?: string | null
+  photoMaker?: { idImages: string[]; styleStrength?: number | null } | null // PhotoMaker 身份图与风格强度
+  circularPadding?: CircularPadding | null // 无缝贴图循环填充方向
+  tilePreview?: string | null // 2x2 平铺预览图路径
+  embeddings?: { prompt: string[]; negativePrompt: string[] } | null // 提示词中引用的嵌入
   generatedAt?: string
   duration?: number // 生成耗时（毫秒）
 }
 
+/**
+ * 生成时实际使用的模型文件
+ */
+export interface ModelFileInfo {
+  path: string
+  size?: number | null
+  /** 文件大小 + 头/中/尾三段 1 MiB 采样的 MD5，仅用于发现文件被替换，并非完整内容哈希 */
+  sampleHash?: string | null
+}
+
+/**
+ * 生成时使用的 SD.cpp 引擎
+ */
+export interface EngineInfo {
+  device: string
+  variant?: string | null
+  /** SD.cpp commit */
+  version?: string | null
+  executable?: string | null
+}
+
 /**
  * 生成的图片对象（包含元数据和预览图片），用于前端显示
  */
 export type GeneratedImage = GeneratedImageInfo
 
+/**
+ * 星级 / 收藏 / 标签筛选条件，图库列表与搜索共用
+ */
+export interface GalleryAnnotationFilter {
+  minRating?: number
+  favorite?: boolean
+  tags?: string[] // 需同时包含全部标签（不区分大小写）
+}
+
+/**
+ * 批量设置星级、收藏、标签与备注；未设置的字段保持不变
+ */
+export interface GalleryAnnotationUpdate {
+  paths: string[]
+  rating?: number // 1-5，0 表示清除
+  favorite?: boolean
+  tags?: string[] // 整体替换标签
+  addTags?: string[]
+  removeTags?: string[]
+  note?: string // 空字符串表示清除
+}
+
+/**
+ * 标签及其使用次数
+ */
+export interface GalleryTagCount {
+  tag: string
+  count: number
+}
+
+/**
+ * 图库搜索请求（基于持久化索引，支持全文检索、筛选、排序与游标分页）
+ */
+export interface GallerySearchRequest extends GalleryAnnotationFilter {
+  query?: string // 在正向 / 反向提示词、标签与备注中全文检索，空格分隔的词需全部匹配
+  groupId?: string
+  samplingMethod?: string
+  width?: number
+  height?: number
+  dateFrom?: number // 修改时间范围（毫秒时间戳，含边界）
+  dateTo?: number
+  mediaType?: 'image' | 'video'
+  deviceType?: string
+  genType?: GenerationType
+  sort?: 'modified' | 'name' | 'size' | 'width' | 'height' | 'steps' | 'seed' | 'rating'
+  order?: 'asc' | 'desc' // 默认 desc
+  cursor?: string // 上一页返回的 nextCursor
+  limit?: number // 每页数量，默认 100，最大 1000
+  refresh?: boolean // 忽略扫描间隔，强制重新扫描目录
+}
+
+/**
+ * 图库搜索结果
+ */
+export interface GallerySearchResult {
+  items: GeneratedImageInfo[]
+  nextCursor?: string | null // 为空表示已是最后一页
+  total: number // 符合筛选条件的总数
+}
+
+/**
+ * 图库目录变化事件（包括应用外部的增删改，已合并防抖）
+ */
+export interface GalleryChangedEvent {
+  added: string[]
+  removed: string[]
+  modified: string[]
+  items: GeneratedImageInfo[] // 新增与修改条目的最新信息
+}
+
+// ─── 收藏集 ─────────────────────────────────────────────────────────────
+
+/**
+ * 收藏集中的条目（按内容哈希跟踪，文件移动后仍可找回）
+ */
+export interface CollectionItem {
+  path: string
+  hash: string // 文件内容的 SHA-256
+  size: number
+  addedAt: string
+}
+
+/**
+ * 收藏集概要
+ */
+export interface CollectionSummary {
+  id: string
+  name: string
+  itemCount: number
+  cover?: string | null // 第一个条目的路径，用作封面
+  createdAt: string
+  updatedAt: string
+}
+
+/**
+ * 收藏集内容
+ */
+export interface CollectionContents {
+  collection: CollectionSummary
+  items: GeneratedImageInfo[]
+  missing: CollectionItem[] // 在图库中找不到文件的条目
+}
+
+/**
+ * 向收藏集添加 / 移除条目的请求
+ */
+export interface CollectionItemsRequest {
+  id: string
+  paths: string[]
+}
+
+// ─── 回收站 ─────────────────────────────────────────────────────────────
+
+/**
+ * 回收站条目来源：图库、权重（含模型组文件）、引擎
+ */
+export type TrashKind = 'gallery' | 'weights' | 'engine'
+
+/**
+ * 回收站中的单个文件
+ */
+export interface TrashedFile {
+  originalPath: string
+  storedName: string // 在条目 files 目录中的文件名
+}
+
+/**
+ * 回收站条目（一次删除操作，包含主文件及其附属文件，如元数据 JSON、校验标记）
+ */
+export interface TrashEntry {
+  id: string
+  kind: TrashKind
+  originalPath: string
+  files: TrashedFile[]
+  size: number // 总字节数
+  deletedAt: number // 删除时间（毫秒时间戳）
+  metadata?: Record<string, unknown> // 删除时附带的信息，如模型组 ID
+}
+
+/**
+ * 回收站自动清理策略（0 表示不限制）
+ */
+export interface TrashPolicy {
+  maxAgeDays: number // 超过此天数的条目将被清理，默认 30
+  maxSizeMb: number // 总大小超出时从最旧的条目开始清理，默认 51200
+}
+
 // ─── SD.cpp 引擎下载 ────────────────────────────────────────────────────
 
 /**
@@ -340,3 +729,57 @@ export interface DownloadConfig {
   /** 最大并发分块数，范围 1-16，默认 4 */
   maxConcurrentChunks: number
 }
+
+// ─── 模型转换 ───────────────────────────────────────────────────────────
+
+/**
+ * 量化类型（sd.cpp -M convert --type）
+ */
+export type QuantizationType =
+  | 'f32'
+  | 'f16'
+  | 'bf16'
+  | 'q8_0'
+  | 'q6_K'
+  | 'q5_K'
+  | 'q5_1'
+  | 'q5_0'
+  | 'q4_K'
+  | 'q4_1'
+  | 'q4_0'
+  | 'q3_K'
+  | 'q2_K'
+
+/**
+ * 模型转换请求
+ */
+export interface ModelConvertRequest {
+  /** 源模型路径（绝对路径、相对权重目录，或模型组目录内的文件名） */
+  inputPath: string
+  /** 目标量化类型 */
+  outputType: QuantizationType
+  /** 输出文件名（默认 `<原文件名>.<类型>.gguf`） */
+  outputName?: string
+  /** 输出到该模型组目录（默认输出到权重目录） */
+  groupId?: string
+  /** 将模型组中引用源文件的字段改为指向转换结果 */
+  updateGroup?: boolean
+  deviceType?: DeviceType | string
+  threads?: number
+  /** 覆盖已存在的输出文件 */
+  overwrite?: boolean
+}
+
+/**
+ * 模型转换进度
+ */
+export interface ModelConvertProgress {
+  /** 当前阶段 */
+  stage: 'converting' | 'done'
+  /** 已处理张量数（解析自 sd.cpp 进度条） */
+  current?: number | null
+  /** 张量总数 */
+  total?: number | null
+  /** 原始输出行 */
+  text: string
+}
@@ -0,0 +1,462 @@
+use crate::state::{self, AppState, GalleryRoot};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+use super::generated_images::{collect_gallery_files, load_media_info, GeneratedImageInfo};
+
+/// A gallery item in a collection. The content hash lets it be found again after the file
+/// is moved or renamed within the gallery
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionItem {
+    pub path: String,
+    /// SHA-256 of the file contents
+    pub hash: String,
+    pub size: u64,
+    pub added_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub items: Vec<CollectionItem>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionSummary {
+    pub id: String,
+    pub name: String,
+    pub item_count: usize,
+    /// First item, for a cover image
+    pub cover: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionRenameRequest {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionItemsRequest {
+    pub id: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionContents {
+    pub collection: CollectionSummary,
+    pub items: Vec<GeneratedImageInfo>,
+    /// Items whose file could not be found anywhere in the gallery
+    pub missing: Vec<CollectionItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct CollectionStore {
+    collections: Vec<Collection>,
+}
+
+/// Collections live next to the gallery index in `<outputs>/.index/collections.json`
+fn store_path(state: &AppState) -> PathBuf {
+    state::get_outputs_folder(state).join(".index").join("collections.json")
+}
+
+fn load_store(state: &AppState) -> CollectionStore {
+    std::fs::read(store_path(state))
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(state: &AppState, store: &CollectionStore) -> Result<(), String> {
+    let path = store_path(state);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+}
+
+/// Load the store, apply `update` and save it, holding the lock so concurrent edits don't
+/// overwrite each other
+fn modify_store<T>(
+    state: &AppState,
+    update: impl FnOnce(&mut CollectionStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = state.collections_lock.lock().unwrap();
+    let mut store = load_store(state);
+    let result = update(&mut store)?;
+    save_store(state, &store)?;
+    Ok(result)
+}
+
+fn find_collection<'a>(store: &'a mut CollectionStore, id: &str) -> Result<&'a mut Collection, String> {
+    store
+        .collections
+        .iter_mut()
+        .find(|c| c.id == id)
+        .ok_or_else(|| format!("Collection not found: {}", id))
+}
+
+fn summarize(collection: &Collection) -> CollectionSummary {
+    CollectionSummary {
+        id: collection.id.clone(),
+        name: collection.name.clone(),
+        item_count: collection.items.len(),
+        cover: collection.items.first().map(|item| item.path.clone()),
+        created_at: collection.created_at.clone(),
+        updated_at: collection.updated_at.clone(),
+    }
+}
+
+/// SHA-256 of a file's contents as lowercase hex
+fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// New paths (by content hash) for items whose file is gone, found among gallery files with
+/// the same content. Only files of the recorded size are hashed
+fn find_relocations(
+    outputs_folder: &Path,
+    roots: &[GalleryRoot],
+    items: &[CollectionItem],
+) -> HashMap<String, String> {
+    let missing: Vec<&CollectionItem> = items.iter().filter(|item| !Path::new(&item.path).is_file()).collect();
+    let mut relocations = HashMap::new();
+    if missing.is_empty() {
+        return relocations;
+    }
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for (path, _) in collect_gallery_files(outputs_folder, roots) {
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            by_size.entry(metadata.len()).or_default().push(path);
+        }
+    }
+
+    let mut hashes: HashMap<PathBuf, String> = HashMap::new();
+    for item in missing {
+        let Some(candidates) = by_size.get(&item.size) else {
+            continue;
+        };
+        let found = candidates.iter().find(|candidate| {
+            let hash = hashes
+                .entry((*candidate).clone())
+                .or_insert_with(|| hash_file(candidate).unwrap_or_default());
+            *hash == item.hash
+        });
+        if let Some(found) = found {
+            relocations.insert(item.hash.clone(), found.to_string_lossy().to_string());
+        }
+    }
+    relocations
+}
+
+/// A collection with moved items pointed at their new location. The gallery is searched on a
+/// blocking thread without holding the store lock; only the path updates are written under it
+async fn load_relocated(state: &AppState, id: &str) -> Result<Collection, String> {
+    let collection = load_store(state)
+        .collections
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| format!("Collection not found: {}", id))?;
+
+    let outputs_folder = state::get_outputs_folder(state);
+    let roots = state.gallery_roots.lock().unwrap().clone();
+    let items = collection.items.clone();
+    let relocations = tokio::task::spawn_blocking(move || find_relocations(&outputs_folder, &roots, &items))
+        .await
+        .map_err(|e| e.to_string())?;
+    if relocations.is_empty() {
+        return Ok(collection);
+    }
+
+    modify_store(state, |store| {
+        let collection = find_collection(store, id)?;
+        for item in collection.items.iter_mut() {
+            if let Some(path) = relocations.get(&item.hash) {
+                item.path = path.clone();
+            }
+        }
+        Ok(collection.clone())
+    })
+}
+
+/// Gallery info for a collection item, marking items under extra gallery roots read-only
+fn item_info(state: &AppState, path: &Path) -> Option<GeneratedImageInfo> {
+    let root = state
+        .gallery_roots
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|root| path.starts_with(&root.path))
+        .map(|root| root.path.clone());
+    load_media_info(path, root)
+}
+
+/// List all collections
+#[tauri::command]
+pub async fn collections_list(state: State<'_, AppState>) -> Result<Vec<CollectionSummary>, String> {
+    Ok(load_store(&state).collections.iter().map(summarize).collect())
+}
+
+/// Create an empty collection
+#[tauri::command]
+pub async fn collections_create(value: String, state: State<'_, AppState>) -> Result<CollectionSummary, String> {
+    let name = value.trim().to_string();
+    if name.is_empty() {
+        return Err("Collection name is required".to_string());
+    }
+    modify_store(&state, |store| {
+        if store.collections.iter().any(|c| c.name == name) {
+            return Err(format!("Collection already exists: {}", name));
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        let collection = Collection {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            items: Vec::new(),
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        let summary = summarize(&collection);
+        store.collections.push(collection);
+        Ok(summary)
+    })
+}
+
+/// Rename a collection
+#[tauri::command]
+pub async fn collections_rename(
+    value: CollectionRenameRequest,
+    state: State<'_, AppState>,
+) -> Result<CollectionSummary, String> {
+    let name = value.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Collection name is required".to_string());
+    }
+    modify_store(&state, |store| {
+        if store.collections.iter().any(|c| c.name == name && c.id != value.id) {
+            return Err(format!("Collection already exists: {}", name));
+        }
+        let collection = find_collection(store, &value.id)?;
+        collection.name = name;
+        collection.updated_at = chrono::Utc::now().to_rfc3339();
+        Ok(summarize(collection))
+    })
+}
+
+/// Delete a collection; the files it references are untouched
+#[tauri::command]
+pub async fn collections_delete(value: String, state: State<'_, AppState>) -> Result<bool, String> {
+    modify_store(&state, |store| {
+        let before = store.collections.len();
+        store.collections.retain(|c| c.id != value);
+        Ok(store.collections.len() != before)
+    })
+}
+
+/// Add gallery items to a collection; items already in it (same content) are skipped
+#[tauri::command]
+pub async fn collections_add_items(
+    value: CollectionItemsRequest,
+    state: State<'_, AppState>,
+) -> Result<CollectionSummary, String> {
+    // Hash outside the store lock; large videos take a while
+    let paths = value.paths.clone();
+    let hashed = tokio::task::spawn_blocking(move || {
+        paths
+            .iter()
+            .map(|path| {
+                let size = std::fs::metadata(path)
+                    .map_err(|e| format!("{}: {}", path, e))?
+                    .len();
+                Ok((path.clone(), hash_file(Path::new(path))?, size))
+            })
+            .collect::<Result<Vec<_>, String>>()
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    modify_store(&state, |store| {
+        let collection = find_collection(store, &value.id)?;
+        let now = chrono::Utc::now().to_rfc3339();
+        for (path, hash, size) in hashed {
+            if collection.items.iter().any(|item| item.hash == hash) {
+                continue;
+            }
+            collection.items.push(CollectionItem {
+                path,
+                hash,
+                size,
+                added_at: now.clone(),
+            });
+        }
+        collection.updated_at = now;
+        Ok(summarize(collection))
+    })
+}
+
+/// Remove items from a collection by path
+#[tauri::command]
+pub async fn collections_remove_items(
+    value: CollectionItemsRequest,
+    state: State<'_, AppState>,
+) -> Result<CollectionSummary, String> {
+    modify_store(&state, |store| {
+        let collection = find_collection(store, &value.id)?;
+        collection.items.retain(|item| !value.paths.contains(&item.path));
+        collection.updated_at = chrono::Utc::now().to_rfc3339();
+        Ok(summarize(collection))
+    })
+}
+
+/// List the items of a collection, following files that moved since they were added
+#[tauri::command]
+pub async fn collections_get_items(
+    value: String,
+    state: State<'_, AppState>,
+) -> Result<CollectionContents, String> {
+    let collection = load_relocated(&state, &value).await?;
+
+    let mut items = Vec::new();
+    let mut missing = Vec::new();
+    for item in &collection.items {
+        match item_info(&state, Path::new(&item.path)) {
+            Some(info) => items.push(info),
+            None => missing.push(item.clone()),
+        }
+    }
+    Ok(CollectionContents {
+        collection: summarize(&collection),
+        items,
+        missing,
+    })
+}
+
+/// Export a collection as a zip of its files and sidecars plus a `manifest.json`
+#[tauri::command]
+pub async fn collections_export(
+    value: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let collection = load_relocated(&state, &value).await?;
+
+    let dest = app
+        .dialog()
+        .file()
+        .set_file_name(format!("{}.zip", collection.name))
+        .add_filter("ZIP", &["zip"])
+        .blocking_save_file();
+    let dest_path = match dest {
+        Some(p) => p.into_path().map_err(|_| "Invalid path".to_string())?,
+        None => {
+            return Ok(serde_json::json!({ "success": false, "canceled": true }));
+        }
+    };
+
+    let file = std::fs::File::create(&dest_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut used_names: Vec<String> = Vec::new();
+    let mut manifest_items = Vec::new();
+    let mut missing = Vec::new();
+    for item in &collection.items {
+        let path = Path::new(&item.path);
+        if !path.is_file() {
+            missing.push(item.path.clone());
+            continue;
+        }
+        // Keep original file names under `items/` (so none can clash with the manifest),
+        // numbering duplicate stems from different folders
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let ext = path.extension().unwrap_or_default().to_string_lossy().to_string();
+        let mut name = stem.clone();
+        let mut counter = 1;
+        while used_names.contains(&name) {
+            name = format!("{}_{}", stem, counter);
+            counter += 1;
+        }
+        used_names.push(name.clone());
+
+        let file_name = format!("items/{}.{}", name, ext);
+        let data = std::fs::read(path).map_err(|e| e.to_string())?;
+        zip.start_file(&file_name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+
+        let sidecar = path.with_extension("json");
+        let sidecar_name = if sidecar.is_file() {
+            let sidecar_name = format!("items/{}.json", name);
+            let data = std::fs::read(&sidecar).map_err(|e| e.to_string())?;
+            zip.start_file(&sidecar_name, options).map_err(|e| e.to_string())?;
+            zip.write_all(&data).map_err(|e| e.to_string())?;
+            Some(sidecar_name)
+        } else {
+            None
+        };
+
+        manifest_items.push(serde_json::json!({
+            "file": file_name,
+            "sidecar": sidecar_name,
+            "sha256": item.hash,
+            "size": item.size,
+            "originalPath": item.path,
+            "addedAt": item.added_at,
+        }));
+    }
+
+    let manifest = serde_json::json!({
+        "name": collection.name,
+        "id": collection.id,
+        "createdAt": collection.created_at,
+        "exportedAt": chrono::Utc::now().to_rfc3339(),
+        "items": manifest_items,
+        "missing": missing,
+    });
+    zip.start_file("manifest.json", options).map_err(|e| e.to_string())?;
+    zip.write_all(serde_json::to_string_pretty(&manifest).unwrap_or_default().as_bytes())
+        .map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+
+    let zip_size = std::fs::metadata(&dest_path).map(|m| m.len()).unwrap_or(0);
+    Ok(serde_json::json!({
+        "success": true,
+        "zipPath": dest_path.to_string_lossy(),
+        "size": zip_size,
+        "missing": missing,
+    }))
+}
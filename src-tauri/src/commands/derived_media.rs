@@ -0,0 +1,81 @@
+use crate::state::{self, AppState};
+use std::path::{Path, PathBuf};
+
+use super::ffmpeg::FfmpegOutcome;
+
+/// Source sidecar fields carried over to derived videos and frames so they show up with their prompt
+pub(crate) const INHERITED_KEYS: &[&str] = &[
+    "prompt",
+    "negativePrompt",
+    "seed",
+    "steps",
+    "cfgScale",
+    "groupId",
+    "groupName",
+    "modelPath",
+    "models",
+];
+
+/// `generated_<ts>.<ext>` in the outputs folder, and the timestamp used
+pub(crate) fn new_output_path(state: &AppState, ext: &str) -> (PathBuf, u128) {
+    let outputs_folder = state::get_outputs_folder(state);
+    let _ = std::fs::create_dir_all(&outputs_folder);
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    (
+        outputs_folder.join(format!("generated_{}.{}", timestamp, ext)),
+        timestamp,
+    )
+}
+
+/// JSON sidecar of a gallery item, or `null` if it has none
+pub(crate) fn read_sidecar(media_path: &Path) -> serde_json::Value {
+    std::fs::read_to_string(media_path.with_extension("json"))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Prompt and model fields of the source, so derived items keep their lineage in the gallery
+pub(crate) fn inherited_metadata(source_meta: &serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+    INHERITED_KEYS
+        .iter()
+        .filter(|key| !source_meta[**key].is_null())
+        .map(|key| (key.to_string(), source_meta[*key].clone()))
+        .collect()
+}
+
+pub(crate) fn extend_metadata(metadata: &mut serde_json::Map<String, serde_json::Value>, extra: serde_json::Value) {
+    if let Some(extra) = extra.as_object() {
+        metadata.extend(extra.clone());
+    }
+}
+
+pub(crate) async fn write_sidecar(media_path: &Path, metadata: &impl serde::Serialize) {
+    let _ = tokio::fs::write(
+        media_path.with_extension("json"),
+        serde_json::to_string_pretty(metadata).unwrap_or_default(),
+    )
+    .await;
+}
+
+/// Remove a failed encode's partial output and describe the failure for the frontend
+pub(crate) async fn encode_failure(output_path: &Path, outcome: &FfmpegOutcome) -> serde_json::Value {
+    let _ = tokio::fs::remove_file(output_path).await;
+    serde_json::json!({
+        "success": false,
+        "error": format!("FFmpeg exited with code: {:?}", outcome.exit_code),
+        "encodeResult": outcome,
+    })
+}
+
+pub(crate) fn video_result(output_path: &Path, started: std::time::Instant) -> serde_json::Value {
+    serde_json::json!({
+        "success": true,
+        "video": format!("media://{}", urlencoding::encode(&output_path.to_string_lossy())),
+        "videoPath": output_path.to_string_lossy(),
+        "duration": started.elapsed().as_millis() as u64,
+    })
+}
@@ -0,0 +1,344 @@
+use crate::state::{self, AppState};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// File extensions sd.cpp loads from `--embd-dir`
+const EMBEDDING_EXTENSIONS: &[&str] = &["safetensors", "pt", "bin", "ckpt"];
+
+/// Upper bound for safetensors headers; real embedding headers are a few hundred bytes
+const MAX_SAFETENSORS_HEADER: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingInfo {
+    /// Trigger word, i.e. the file stem referenced in prompts
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub format: String,
+    /// Number of tokens (vectors) the embedding expands to, when the header could be parsed
+    pub tokens: Option<u64>,
+}
+
+/// Get the effective embeddings folder (custom folder or `<models>/embeddings`)
+#[tauri::command]
+pub async fn embeddings_get_folder(state: State<'_, AppState>) -> Result<String, String> {
+    Ok(resolve_embeddings_folder(&state).to_string_lossy().to_string())
+}
+
+/// Set the embeddings folder; an empty value restores the default
+#[tauri::command]
+pub async fn embeddings_set_folder(value: String, state: State<'_, AppState>) -> Result<bool, String> {
+    if value.trim().is_empty() {
+        *state.embeddings_folder.lock().unwrap() = None;
+        return Ok(true);
+    }
+    if !Path::new(&value).is_dir() {
+        return Ok(false);
+    }
+    *state.embeddings_folder.lock().unwrap() = Some(value);
+    Ok(true)
+}
+
+/// List embeddings with their token counts for prompt autocompletion
+#[tauri::command]
+pub async fn embeddings_list(state: State<'_, AppState>) -> Result<Vec<EmbeddingInfo>, String> {
+    let folder = resolve_embeddings_folder(&state);
+    let mut embeddings: Vec<EmbeddingInfo> = list_embedding_files(&folder)
+        .into_iter()
+        .map(|(name, path)| {
+            let format = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let tokens = match format.as_str() {
+                "safetensors" => read_safetensors_tokens(&path),
+                _ => read_pickle_tokens(&path),
+            };
+            EmbeddingInfo {
+                name,
+                size: std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                path: path.to_string_lossy().to_string(),
+                format,
+                tokens,
+            }
+        })
+        .collect();
+    embeddings.sort_by_key(|e| e.name.to_lowercase());
+    Ok(embeddings)
+}
+
+/// Embeddings folder from state, falling back to `embeddings` under the active models folder
+pub(crate) fn resolve_embeddings_folder(state: &AppState) -> PathBuf {
+    match state.embeddings_folder.lock().unwrap().clone() {
+        Some(folder) if !folder.trim().is_empty() => PathBuf::from(folder),
+        _ => {
+            let weights_folder = state.weights_folder.lock().unwrap().clone();
+            state::get_active_models_folder(weights_folder.as_deref()).join("embeddings")
+        }
+    }
+}
+
+/// Embedding files directly inside `folder`, as (name, path) pairs
+pub(crate) fn list_embedding_files(folder: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .map(|e| EMBEDDING_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            Some((name, path))
+        })
+        .collect()
+}
+
+/// Names of embeddings that appear as whole words in `text`
+pub(crate) fn referenced_embeddings(text: &str, names: &[(String, PathBuf)]) -> Vec<String> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-' || c == '.';
+    let mut found: Vec<String> = names
+        .iter()
+        .filter(|(name, _)| {
+            text.match_indices(name.as_str()).any(|(start, matched)| {
+                let before = text[..start].chars().next_back();
+                let after = text[start + matched.len()..].chars().next();
+                !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+            })
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Token count from a safetensors header: the leading dimension of the first 2-D tensor
+/// (SDXL embeddings carry `clip_l`/`clip_g` with the same token count)
+fn read_safetensors_tokens(path: &Path) -> Option<u64> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut len_bytes = [0u8; 8];
+    file.read_exact(&mut len_bytes).ok()?;
+    let header_len = u64::from_le_bytes(len_bytes);
+    if header_len == 0 || header_len > MAX_SAFETENSORS_HEADER {
+        return None;
+    }
+    let mut header = vec![0u8; header_len as usize];
+    file.read_exact(&mut header).ok()?;
+    let header: serde_json::Value = serde_json::from_slice(&header).ok()?;
+
+    let shapes: Vec<Vec<u64>> = header
+        .as_object()?
+        .iter()
+        .filter(|(key, _)| key.as_str() != "__metadata__")
+        .filter_map(|(_, tensor)| serde_json::from_value(tensor["shape"].clone()).ok())
+        .collect();
+    shapes
+        .iter()
+        .find(|shape| shape.len() == 2)
+        .map(|shape| shape[0])
+        .or_else(|| shapes.iter().find(|shape| shape.len() == 1).map(|_| 1))
+}
+
+/// Token count from a PyTorch checkpoint (zip archive or legacy raw pickle):
+/// the leading dimension of the first tensor in the pickle
+fn read_pickle_tokens(path: &Path) -> Option<u64> {
+    let file = std::fs::File::open(path).ok()?;
+    let pickle = match zip::ZipArchive::new(file) {
+        Ok(mut archive) => {
+            let name = archive
+                .file_names()
+                .find(|n| n.ends_with("data.pkl"))?
+                .to_string();
+            let mut data = Vec::new();
+            archive.by_name(&name).ok()?.read_to_end(&mut data).ok()?;
+            data
+        }
+        Err(_) => {
+            let mut data = Vec::new();
+            std::fs::File::open(path)
+                .ok()?
+                .take(MAX_SAFETENSORS_HEADER)
+                .read_to_end(&mut data)
+                .ok()?;
+            data
+        }
+    };
+    let shape = first_tensor_shape(&pickle)?;
+    match shape.len() {
+        0 => None,
+        1 => Some(1),
+        _ => Some(shape[0]),
+    }
+}
+
+/// Stack items tracked while scanning a pickle; everything but ints is opaque
+enum PickleItem {
+    Mark,
+    Int(i64),
+    Other,
+}
+
+/// Walk pickle opcodes and return the size tuple passed to the first
+/// `torch._utils._rebuild_tensor_v2` call. Only the opcodes torch emits are understood.
+fn first_tensor_shape(data: &[u8]) -> Option<Vec<u64>> {
+    let mut stack: Vec<PickleItem> = Vec::new();
+    let mut seen_rebuild = false;
+    let mut pos = 0usize;
+
+    let take = |pos: &mut usize, n: usize| -> Option<&[u8]> {
+        let slice = data.get(*pos..*pos + n)?;
+        *pos += n;
+        Some(slice)
+    };
+    let read_u32 = |bytes: &[u8]| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+
+    while pos < data.len() {
+        let op = data[pos];
+        pos += 1;
+        match op {
+            // STOP; legacy (non-zip) checkpoints hold several pickles back to back
+            b'.' => stack.clear(),
+            // PROTO, BINPUT, BINGET
+            0x80 | b'q' | b'h' => {
+                take(&mut pos, 1)?;
+                if op == b'h' {
+                    stack.push(PickleItem::Other);
+                }
+            }
+            // LONG_BINPUT, LONG_BINGET
+            b'r' | b'j' => {
+                take(&mut pos, 4)?;
+                if op == b'j' {
+                    stack.push(PickleItem::Other);
+                }
+            }
+            // FRAME
+            0x95 => {
+                take(&mut pos, 8)?;
+            }
+            // MEMOIZE
+            0x94 => {}
+            // GLOBAL: "module\nname\n"
+            b'c' => {
+                let rest = data.get(pos..)?;
+                let first = rest.iter().position(|&b| b == b'\n')?;
+                let second = rest[first + 1..].iter().position(|&b| b == b'\n')?;
+                let name = &rest[first + 1..first + 1 + second];
+                if name.starts_with(b"_rebuild_tensor") {
+                    seen_rebuild = true;
+                }
+                pos += first + second + 2;
+                stack.push(PickleItem::Other);
+            }
+            // STACK_GLOBAL
+            0x93 => {
+                stack.truncate(stack.len().saturating_sub(2));
+                stack.push(PickleItem::Other);
+            }
+            b'(' => stack.push(PickleItem::Mark),
+            b'K' => {
+                let b = take(&mut pos, 1)?;
+                stack.push(PickleItem::Int(b[0] as i64));
+            }
+            b'M' => {
+                let b = take(&mut pos, 2)?;
+                stack.push(PickleItem::Int(u16::from_le_bytes([b[0], b[1]]) as i64));
+            }
+            b'J' => {
+                let b = take(&mut pos, 4)?;
+                stack.push(PickleItem::Int(i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as i64));
+            }
+            // LONG1
+            0x8a => {
+                let n = take(&mut pos, 1)?[0] as usize;
+                let b = take(&mut pos, n)?;
+                let mut v: i64 = 0;
+                for (i, byte) in b.iter().take(8).enumerate() {
+                    v |= (*byte as i64) << (8 * i);
+                }
+                stack.push(PickleItem::Int(v));
+            }
+            // BINUNICODE, BINBYTES, BINSTRING
+            b'X' | b'B' | b'T' => {
+                let n = read_u32(take(&mut pos, 4)?);
+                take(&mut pos, n)?;
+                stack.push(PickleItem::Other);
+            }
+            // SHORT_BINUNICODE, SHORT_BINBYTES, SHORT_BINSTRING
+            0x8c | b'C' | b'U' => {
+                let n = take(&mut pos, 1)?[0] as usize;
+                take(&mut pos, n)?;
+                stack.push(PickleItem::Other);
+            }
+            // BINFLOAT
+            b'G' => {
+                take(&mut pos, 8)?;
+                stack.push(PickleItem::Other);
+            }
+            // NONE, NEWTRUE, NEWFALSE, EMPTY_DICT, EMPTY_LIST, EMPTY_TUPLE
+            b'N' | 0x88 | 0x89 | b'}' | b']' | b')' => stack.push(PickleItem::Other),
+            // TUPLE1..3
+            0x85..=0x87 => {
+                let n = (op - 0x84) as usize;
+                let items = stack.split_off(stack.len().saturating_sub(n));
+                let tuple = int_tuple(&items);
+                if seen_rebuild && tuple.is_some() {
+                    return tuple;
+                }
+                stack.push(PickleItem::Other);
+            }
+            // TUPLE, LIST, DICT: collapse to the last mark
+            b't' | b'l' | b'd' => {
+                let mark = stack.iter().rposition(|i| matches!(i, PickleItem::Mark))?;
+                let items = stack.split_off(mark + 1);
+                stack.pop();
+                let tuple = if op == b't' { int_tuple(&items) } else { None };
+                if seen_rebuild && tuple.is_some() {
+                    return tuple;
+                }
+                stack.push(PickleItem::Other);
+            }
+            // SETITEMS, APPENDS: drop back to the mark
+            b'u' | b'e' => {
+                let mark = stack.iter().rposition(|i| matches!(i, PickleItem::Mark))?;
+                stack.truncate(mark);
+            }
+            // SETITEM
+            b's' => stack.truncate(stack.len().saturating_sub(2)),
+            // APPEND, BINPERSID
+            b'a' | b'Q' => {
+                stack.pop();
+                if op == b'Q' {
+                    stack.push(PickleItem::Other);
+                }
+            }
+            // REDUCE, BUILD, NEWOBJ
+            b'R' | b'b' | 0x81 => {
+                stack.truncate(stack.len().saturating_sub(2));
+                stack.push(PickleItem::Other);
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Convert a run of stack items to an int tuple if every item is a non-negative int
+fn int_tuple(items: &[PickleItem]) -> Option<Vec<u64>> {
+    items
+        .iter()
+        .map(|item| match item {
+            PickleItem::Int(v) if *v >= 0 => Some(*v as u64),
+            _ => None,
+        })
+        .collect()
+}
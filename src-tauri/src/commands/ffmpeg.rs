@@ -0,0 +1,729 @@
+use crate::state::{self, AppState, CancelGuard};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter, State};
+
+use super::generate::configure_command;
+use super::sdcpp::{extract_zip, SDCppDownloadProgress};
+
+/// Number of stderr lines kept when reporting FFmpeg failures
+const STDERR_TAIL_LINES: usize = 20;
+
+/// x264/x265 speed presets
+const X26X_PRESETS: &[&str] = &[
+    "ultrafast", "superfast", "veryfast", "faster", "fast", "medium", "slow", "slower", "veryslow",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegCandidate {
+    pub path: String,
+    /// "selected", "bundled" or "system"
+    pub source: String,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegCodec {
+    pub name: String,
+    /// "video", "audio" or "subtitle"
+    pub kind: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegInfo {
+    pub path: String,
+    pub version: Option<String>,
+    pub encoders: Vec<FfmpegCodec>,
+}
+
+/// Result of an FFmpeg run, surfaced to the frontend as-is
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegOutcome {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    /// Last lines of FFmpeg's stderr
+    pub stderr: String,
+}
+
+/// Run FFmpeg with the given arguments, capturing stderr for error reporting
+pub(crate) async fn run_ffmpeg(ffmpeg_path: &Path, args: &[String]) -> Result<FfmpegOutcome, String> {
+    let mut cmd = tokio::process::Command::new(ffmpeg_path);
+    cmd.arg("-hide_banner")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    configure_command(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr.lines().collect();
+    let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n");
+    Ok(FfmpegOutcome {
+        success: output.status.success(),
+        exit_code: output.status.code(),
+        stderr: tail,
+    })
+}
+
+fn ffmpeg_executable_name() -> &'static str {
+    if cfg!(target_os = "windows") { "ffmpeg.exe" } else { "ffmpeg" }
+}
+
+/// First `ffmpeg` found on PATH
+fn find_system_ffmpeg() -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(ffmpeg_executable_name()))
+        .find(|candidate| candidate.is_file())
+}
+
+/// FFmpeg to use: the selected executable, then the bundled build, then the system one on PATH
+pub(crate) fn resolve_ffmpeg_path(state: &AppState) -> Result<PathBuf, String> {
+    if let Some(selected) = state.ffmpeg_path.lock().unwrap().clone() {
+        if Path::new(&selected).is_file() {
+            return Ok(PathBuf::from(selected));
+        }
+    }
+    let bundled = state::get_ffmpeg_path();
+    if bundled.is_file() {
+        return Ok(bundled);
+    }
+    find_system_ffmpeg().ok_or_else(|| "未找到 FFmpeg：请在设置中下载、导入或选择 FFmpeg".to_string())
+}
+
+/// Version string from the first line of `ffmpeg -version`, e.g. "6.1.1-static"
+async fn get_ffmpeg_version(ffmpeg_path: &Path) -> Option<String> {
+    let mut cmd = tokio::process::Command::new(ffmpeg_path);
+    cmd.arg("-version").stdin(Stdio::null());
+    configure_command(&mut cmd);
+    let output = tokio::time::timeout(std::time::Duration::from_secs(10), cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .next()?
+        .strip_prefix("ffmpeg version ")?
+        .split_whitespace()
+        .next()
+        .map(|v| v.to_string())
+}
+
+/// Parse `ffmpeg -encoders` output: a legend, a `------` separator, then ` V....D name  description`
+fn parse_encoders(output: &str) -> Vec<FfmpegCodec> {
+    output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("------"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let flags = parts.next()?;
+            let name = parts.next()?;
+            let kind = match flags.chars().next()? {
+                'V' => "video",
+                'A' => "audio",
+                'S' => "subtitle",
+                _ => return None,
+            };
+            Some(FfmpegCodec {
+                name: name.to_string(),
+                kind: kind.to_string(),
+                description: parts.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect()
+}
+
+/// List usable FFmpeg executables: the selected one, the bundled build and the system one
+#[tauri::command]
+pub async fn ffmpeg_detect(state: State<'_, AppState>) -> Result<Vec<FfmpegCandidate>, String> {
+    let selected = state.ffmpeg_path.lock().unwrap().clone();
+    let mut candidates: Vec<(PathBuf, &str)> = Vec::new();
+    if let Some(selected) = selected.filter(|p| Path::new(p).is_file()) {
+        candidates.push((PathBuf::from(selected), "selected"));
+    }
+    let bundled = state::get_ffmpeg_path();
+    if bundled.is_file() {
+        candidates.push((bundled, "bundled"));
+    }
+    if let Some(system) = find_system_ffmpeg() {
+        candidates.push((system, "system"));
+    }
+
+    let mut result = Vec::new();
+    for (path, source) in candidates {
+        if result.iter().any(|c: &FfmpegCandidate| Path::new(&c.path) == path) {
+            continue;
+        }
+        result.push(FfmpegCandidate {
+            version: get_ffmpeg_version(&path).await,
+            path: path.to_string_lossy().to_string(),
+            source: source.to_string(),
+        });
+    }
+    Ok(result)
+}
+
+/// Report the version and encoder list of an FFmpeg executable (the active one by default)
+#[tauri::command]
+pub async fn ffmpeg_get_info(
+    value: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FfmpegInfo, String> {
+    let ffmpeg_path = match value.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => resolve_ffmpeg_path(&state)?,
+    };
+    let version = get_ffmpeg_version(&ffmpeg_path).await;
+
+    let mut cmd = tokio::process::Command::new(&ffmpeg_path);
+    cmd.args(["-hide_banner", "-encoders"]).stdin(Stdio::null());
+    configure_command(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+
+    Ok(FfmpegInfo {
+        path: ffmpeg_path.to_string_lossy().to_string(),
+        version,
+        encoders: parse_encoders(&String::from_utf8_lossy(&output.stdout)),
+    })
+}
+
+/// Get the FFmpeg executable that will be used, if any
+#[tauri::command]
+pub async fn ffmpeg_get_path(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(resolve_ffmpeg_path(&state)
+        .ok()
+        .map(|p| p.to_string_lossy().to_string()))
+}
+
+/// Choose the FFmpeg executable; an empty value restores automatic selection
+#[tauri::command]
+pub async fn ffmpeg_set_path(value: String, state: State<'_, AppState>) -> Result<bool, String> {
+    if value.trim().is_empty() {
+        *state.ffmpeg_path.lock().unwrap() = None;
+        return Ok(true);
+    }
+    if get_ffmpeg_version(Path::new(&value)).await.is_none() {
+        return Ok(false);
+    }
+    *state.ffmpeg_path.lock().unwrap() = Some(value);
+    Ok(true)
+}
+
+/// Default static build for the current platform
+fn default_ffmpeg_download_url() -> Option<&'static str> {
+    if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+        Some(concat!(
+            "https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/",
+            "ffmpeg-master-latest-win64-gpl.zip"
+        ))
+    } else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some(concat!(
+            "https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/",
+            "ffmpeg-master-latest-linux64-gpl.tar.xz"
+        ))
+    } else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+        Some(concat!(
+            "https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/",
+            "ffmpeg-master-latest-linuxarm64-gpl.tar.xz"
+        ))
+    } else if cfg!(target_os = "macos") {
+        Some("https://evermeet.cx/ffmpeg/getrelease/zip")
+    } else {
+        None
+    }
+}
+
+/// Download a static FFmpeg build and install it into engines/ffmpeg/bin
+#[tauri::command]
+pub async fn ffmpeg_download(
+    value: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let url = match value.filter(|u| !u.trim().is_empty()) {
+        Some(url) => url,
+        None => default_ffmpeg_download_url()
+            .ok_or("No prebuilt FFmpeg for this platform; import one instead")?
+            .to_string(),
+    };
+    let file_name = url
+        .rsplit('/')
+        .next()
+        .filter(|n| n.contains('.'))
+        .unwrap_or("ffmpeg.zip")
+        .to_string();
+
+    let engine_folder = ffmpeg_engine_folder();
+    std::fs::create_dir_all(&engine_folder).map_err(|e| e.to_string())?;
+    let archive_path = engine_folder.join(&file_name);
+
+    let (cancel_tx, mut cancel_rx) = tokio::sync::watch::channel(false);
+    let cancel_guard = CancelGuard::install(&state.ffmpeg_download_cancel, cancel_tx);
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header("User-Agent", "HelloUI")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let total_bytes = response.content_length().unwrap_or(0);
+    let mut downloaded_bytes: u64 = 0;
+    let mut file = tokio::fs::File::create(&archive_path).await.map_err(|e| e.to_string())?;
+
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    let mut stream = response.bytes_stream();
+    let start_time = std::time::Instant::now();
+    let mut last_emit = std::time::Instant::now();
+    loop {
+        tokio::select! {
+            chunk = stream.next() => {
+                match chunk {
+                    Some(Ok(data)) => {
+                        file.write_all(&data).await.map_err(|e| e.to_string())?;
+                        downloaded_bytes += data.len() as u64;
+                        // Throttle progress events like the SD.cpp engine download
+                        let now = std::time::Instant::now();
+                        if now.duration_since(last_emit) >= std::time::Duration::from_millis(200) {
+                            let elapsed = start_time.elapsed().as_secs_f64();
+                            let _ = app.emit(
+                                "ffmpeg:download-progress",
+                                SDCppDownloadProgress {
+                                    stage: "downloading".to_string(),
+                                    downloaded_bytes,
+                                    total_bytes,
+                                    speed: if elapsed > 0.0 { downloaded_bytes as f64 / elapsed } else { 0.0 },
+                                    file_name: file_name.clone(),
+                                    error: None,
+                                },
+                            );
+                            last_emit = now;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        drop(file);
+                        tokio::fs::remove_file(&archive_path).await.ok();
+                        return Err(e.to_string());
+                    }
+                    None => break,
+                }
+            }
+            _ = cancel_rx.changed() => {
+                drop(file);
+                tokio::fs::remove_file(&archive_path).await.ok();
+                return Ok(serde_json::json!({ "success": false, "error": "cancelled" }));
+            }
+        }
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+    drop(cancel_guard);
+
+    let _ = app.emit(
+        "ffmpeg:download-progress",
+        SDCppDownloadProgress {
+            stage: "extracting".to_string(),
+            downloaded_bytes,
+            total_bytes,
+            speed: 0.0,
+            file_name: file_name.clone(),
+            error: None,
+        },
+    );
+    let installed = install_ffmpeg_archive(&archive_path).await;
+    let _ = std::fs::remove_file(&archive_path);
+    let installed = installed?;
+
+    let _ = app.emit(
+        "ffmpeg:download-progress",
+        SDCppDownloadProgress {
+            stage: "done".to_string(),
+            downloaded_bytes,
+            total_bytes,
+            speed: 0.0,
+            file_name,
+            error: None,
+        },
+    );
+
+    Ok(serde_json::json!({
+        "success": true,
+        "path": installed.to_string_lossy(),
+        "version": get_ffmpeg_version(&installed).await,
+    }))
+}
+
+/// Cancel an FFmpeg download
+#[tauri::command]
+pub async fn ffmpeg_cancel_download(state: State<'_, AppState>) -> Result<bool, String> {
+    if let Some(cancel) = state.ffmpeg_download_cancel.lock().unwrap().take() {
+        let _ = cancel.send(true);
+    }
+    Ok(true)
+}
+
+/// Import FFmpeg from a local archive (.zip / .tar.xz / .tar.gz / .7z via tar) or executable
+#[tauri::command]
+pub async fn ffmpeg_import(value: String) -> Result<serde_json::Value, String> {
+    let source = Path::new(&value);
+    if !source.is_file() {
+        return Err(format!("File not found: {}", value));
+    }
+    let is_executable = source
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case(ffmpeg_executable_name()));
+
+    let installed = if is_executable {
+        let bin_dir = ffmpeg_engine_folder().join("bin");
+        std::fs::create_dir_all(&bin_dir).map_err(|e| e.to_string())?;
+        install_binaries(source.parent().unwrap_or(Path::new(".")), &bin_dir)?
+    } else {
+        install_ffmpeg_archive(source).await?
+    };
+    let version = get_ffmpeg_version(&installed).await;
+    if version.is_none() {
+        return Err(format!("Imported file is not a working FFmpeg: {}", installed.display()));
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "path": installed.to_string_lossy(),
+        "version": version,
+    }))
+}
+
+fn ffmpeg_engine_folder() -> PathBuf {
+    state::get_ffmpeg_path()
+        .parent()
+        .and_then(|bin| bin.parent())
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| state::get_run_path().join("engines").join("ffmpeg"))
+}
+
+/// Extract an FFmpeg archive and copy ffmpeg/ffprobe into engines/ffmpeg/bin
+async fn install_ffmpeg_archive(archive_path: &Path) -> Result<PathBuf, String> {
+    let engine_folder = ffmpeg_engine_folder();
+    let extract_dir = engine_folder.join(format!(".extract-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&extract_dir).map_err(|e| e.to_string())?;
+
+    let name = archive_path.to_string_lossy().to_lowercase();
+    let extracted: Result<(), String> = if name.ends_with(".zip") || !name.contains(".tar") {
+        // evermeet.cx serves zips without an extension in the URL
+        extract_zip(archive_path, &extract_dir).map(|_| ())
+    } else {
+        // No xz/gzip decoder in-tree; system tar (also shipped with Windows 10+) handles both
+        let mut cmd = tokio::process::Command::new("tar");
+        cmd.arg("-xf").arg(archive_path).arg("-C").arg(&extract_dir);
+        configure_command(&mut cmd);
+        match cmd.output().await {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => Err(format!(
+                "Failed to extract {}: {}",
+                archive_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+            Err(e) => Err(format!("Failed to start tar: {}", e)),
+        }
+    };
+
+    let bin_dir = engine_folder.join("bin");
+    let installed = extracted.and_then(|_| {
+        let ffmpeg = find_file(&extract_dir, ffmpeg_executable_name())
+            .ok_or("Archive does not contain an FFmpeg executable")?;
+        std::fs::create_dir_all(&bin_dir).map_err(|e| e.to_string())?;
+        install_binaries(ffmpeg.parent().unwrap_or(&extract_dir), &bin_dir)
+    });
+    let _ = std::fs::remove_dir_all(&extract_dir);
+    installed
+}
+
+/// Copy ffmpeg (and ffprobe when present) from `src_dir` into `bin_dir`; returns the ffmpeg path
+fn install_binaries(src_dir: &Path, bin_dir: &Path) -> Result<PathBuf, String> {
+    let ext = if cfg!(target_os = "windows") { ".exe" } else { "" };
+    let mut installed = None;
+    for tool in ["ffmpeg", "ffprobe"] {
+        let src = src_dir.join(format!("{}{}", tool, ext));
+        if !src.is_file() {
+            continue;
+        }
+        let dest = bin_dir.join(format!("{}{}", tool, ext));
+        if src != dest {
+            std::fs::copy(&src, &dest).map_err(|e| e.to_string())?;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&dest, std::fs::Permissions::from_mode(0o755));
+        }
+        if tool == "ffmpeg" {
+            installed = Some(dest);
+        }
+    }
+    installed.ok_or_else(|| format!("FFmpeg executable not found in: {}", src_dir.display()))
+}
+
+fn find_file(dir: &Path, file_name: &str) -> Option<PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(&path, file_name) {
+                return Some(found);
+            }
+        } else if path.file_name().is_some_and(|n| n == file_name) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+impl FfmpegOutcome {
+    /// Turn a failed run into an error carrying the exit status and stderr tail
+    pub(crate) fn into_result(self) -> Result<(), String> {
+        if self.success {
+            Ok(())
+        } else {
+            Err(format!("FFmpeg exited with code: {:?}\n{}", self.exit_code, self.stderr.trim()))
+        }
+    }
+}
+
+/// Stream parameters of a video's first video stream
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoProbe {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    pub frame_count: Option<u64>,
+    pub codec: Option<String>,
+    /// Seconds
+    pub duration: Option<f64>,
+}
+
+/// `ffprobe` next to the given FFmpeg executable, as shipped in FFmpeg builds
+fn ffprobe_sibling(ffmpeg_path: &Path) -> Option<PathBuf> {
+    let name = if cfg!(target_os = "windows") { "ffprobe.exe" } else { "ffprobe" };
+    Some(ffmpeg_path.parent()?.join(name)).filter(|path| path.is_file())
+}
+
+/// Probe a video with ffprobe when it sits next to FFmpeg, otherwise from `ffmpeg -i` output.
+/// The frame count is estimated from duration and frame rate when the container doesn't store it
+pub(crate) async fn probe_video(ffmpeg_path: &Path, video_path: &Path) -> Option<VideoProbe> {
+    let probe = match ffprobe_sibling(ffmpeg_path) {
+        Some(ffprobe) => probe_with_ffprobe(&ffprobe, video_path).await,
+        None => None,
+    };
+    let probe = match probe {
+        Some(probe) => Some(probe),
+        None => probe_with_ffmpeg(ffmpeg_path, video_path).await,
+    };
+    probe.map(|mut probe| {
+        if probe.frame_count.is_none() {
+            if let (Some(duration), Some(fps)) = (probe.duration, probe.fps) {
+                probe.frame_count = Some((duration * fps).round() as u64);
+            }
+        }
+        probe
+    })
+}
+
+async fn probe_with_ffprobe(ffprobe_path: &Path, video_path: &Path) -> Option<VideoProbe> {
+    let mut cmd = tokio::process::Command::new(ffprobe_path);
+    cmd.args([
+        "-v",
+        "error",
+        "-select_streams",
+        "v:0",
+        "-show_entries",
+        "stream=codec_name,width,height,avg_frame_rate,r_frame_rate,nb_frames,duration:format=duration",
+        "-of",
+        "json",
+    ])
+    .arg(video_path)
+    .stdin(Stdio::null());
+    configure_command(&mut cmd);
+    let output = cmd.output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let stream = json["streams"].get(0)?;
+
+    // ffprobe reports rates as fractions ("16/1", "30000/1001") and most numbers as strings
+    let rate = |value: &serde_json::Value| {
+        let (num, den) = value.as_str()?.split_once('/')?;
+        let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+        Some(num / den).filter(|fps| den > 0.0 && *fps > 0.0)
+    };
+    let number = |value: &serde_json::Value| value.as_str()?.parse::<f64>().ok();
+    Some(VideoProbe {
+        width: stream["width"].as_u64().map(|v| v as u32),
+        height: stream["height"].as_u64().map(|v| v as u32),
+        fps: rate(&stream["avg_frame_rate"]).or_else(|| rate(&stream["r_frame_rate"])),
+        frame_count: number(&stream["nb_frames"]).map(|v| v as u64).filter(|v| *v > 0),
+        codec: stream["codec_name"].as_str().map(|s| s.to_string()),
+        duration: number(&stream["duration"]).or_else(|| number(&json["format"]["duration"])),
+    })
+}
+
+/// Parse `ffmpeg -i` stream info, e.g. `Duration: 00:00:02.00, ...` and
+/// `Stream #0:0: Video: mjpeg ..., 640x480, 16 fps, 16 tbr`
+async fn probe_with_ffmpeg(ffmpeg_path: &Path, video_path: &Path) -> Option<VideoProbe> {
+    let mut cmd = tokio::process::Command::new(ffmpeg_path);
+    cmd.arg("-hide_banner").arg("-i").arg(video_path).stdin(Stdio::null());
+    configure_command(&mut cmd);
+    let output = cmd.output().await.ok()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stream = stderr.lines().find(|line| line.contains("Video:"))?;
+    let fields: Vec<&str> = stream.split(',').map(|field| field.trim()).collect();
+
+    let fps = ["fps", "tbr"].iter().find_map(|unit| {
+        fields.iter().find_map(|field| {
+            field
+                .strip_suffix(unit)?
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|fps| *fps > 0.0)
+        })
+    });
+    let (width, height) = fields
+        .iter()
+        .find_map(|field| {
+            let (w, h) = field.split_whitespace().next()?.split_once('x')?;
+            Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?))
+        })
+        .unzip();
+    let codec = stream
+        .split("Video:")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|codec| codec.trim_end_matches(',').to_string());
+    let duration = stderr.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("Duration:")?.split(',').next()?.trim();
+        let mut parts = value.split(':').map(|part| part.parse::<f64>().ok());
+        let (h, m, s) = (parts.next()??, parts.next()??, parts.next()??);
+        Some(h * 3600.0 + m * 60.0 + s)
+    });
+
+    Some(VideoProbe {
+        width,
+        height,
+        fps,
+        frame_count: None,
+        codec,
+        duration,
+    })
+}
+
+/// Video encoding profile for transcoding sd.cpp's AVI output
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VideoEncodeProfile {
+    /// "h264" (default), "h265", "vp9" or "av1"
+    pub codec: Option<String>,
+    /// "mp4" or "webm"; defaults to webm for VP9 and mp4 otherwise
+    pub container: Option<String>,
+    pub crf: Option<u32>,
+    /// x264/x265 speed preset
+    pub preset: Option<String>,
+    /// Defaults to yuv420p, which browsers can play
+    pub pixel_format: Option<String>,
+    /// Keep the original AVI next to the encoded file, as `generated_<ts>_original.avi` with
+    /// its own sidecar
+    pub keep_avi: bool,
+}
+
+impl VideoEncodeProfile {
+    /// Codec/quality arguments and the output file extension
+    pub(crate) fn encode_args(&self) -> Result<(Vec<String>, &'static str), String> {
+        let codec = self.codec.as_deref().unwrap_or("h264");
+        let container = match self.container.as_deref() {
+            Some("mp4") => "mp4",
+            Some("webm") => "webm",
+            Some(other) => return Err(format!("Unsupported container: {} (expected mp4 or webm)", other)),
+            None if codec == "vp9" => "webm",
+            None => "mp4",
+        };
+
+        let (encoder, default_crf, max_crf) = match codec {
+            "h264" => ("libx264", 23, 51),
+            "h265" => ("libx265", 28, 51),
+            "vp9" => ("libvpx-vp9", 32, 63),
+            "av1" => ("libaom-av1", 30, 63),
+            other => {
+                return Err(format!(
+                    "Unsupported codec: {} (expected h264, h265, vp9 or av1)",
+                    other
+                ));
+            }
+        };
+        if container == "webm" && !matches!(codec, "vp9" | "av1") {
+            return Err(format!("WebM requires VP9 or AV1, not {}", codec));
+        }
+
+        let crf = self.crf.unwrap_or(default_crf);
+        if crf > max_crf {
+            return Err(format!("CRF for {} must be between 0 and {}", codec, max_crf));
+        }
+
+        let mut args = vec![
+            "-c:v".to_string(),
+            encoder.to_string(),
+            "-crf".to_string(),
+            crf.to_string(),
+        ];
+        match codec {
+            "h264" | "h265" => {
+                let preset = self.preset.as_deref().unwrap_or("fast");
+                if !X26X_PRESETS.contains(&preset) {
+                    return Err(format!(
+                        "Unsupported preset: {} (expected one of: {})",
+                        preset,
+                        X26X_PRESETS.join(", ")
+                    ));
+                }
+                args.extend(["-preset".to_string(), preset.to_string()]);
+                if codec == "h265" {
+                    // Safari/QuickTime only play HEVC tagged as hvc1
+                    args.extend(["-tag:v".to_string(), "hvc1".to_string()]);
+                }
+            }
+            // Constant-quality mode for libvpx/libaom
+            _ => args.extend(["-b:v".to_string(), "0".to_string()]),
+        }
+        if codec == "av1" {
+            args.extend(["-cpu-used".to_string(), "6".to_string(), "-row-mt".to_string(), "1".to_string()]);
+        }
+
+        args.extend([
+            "-pix_fmt".to_string(),
+            self.pixel_format.clone().unwrap_or_else(|| "yuv420p".to_string()),
+        ]);
+        if container == "mp4" {
+            args.extend(["-movflags".to_string(), "+faststart".to_string()]);
+        }
+
+        Ok((args, container))
+    }
+}
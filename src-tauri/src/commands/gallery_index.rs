@@ -0,0 +1,515 @@
+use crate::state::{self, AppState};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use super::ffmpeg::resolve_ffmpeg_path;
+use super::generated_images::{
+    apply_video_details, collect_gallery_files, load_media_info, GalleryAnnotationFilter,
+    GeneratedImageInfo,
+};
+
+/// Bump when the entry layout changes so old index files are rebuilt
+const INDEX_VERSION: u32 = 2;
+
+/// Searches within this interval reuse the last scan instead of stat-ing every file again.
+/// The file watcher keeps the index current in between, so this is only a safety net
+const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// An indexed gallery item with the modification time of its sidecar
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryIndexEntry {
+    pub sidecar_modified: u64,
+    pub info: GeneratedImageInfo,
+    /// Lowercased prompt, negative prompt, tags and note for full-text search
+    #[serde(skip)]
+    search_text: String,
+}
+
+impl GalleryIndexEntry {
+    fn new(info: GeneratedImageInfo, sidecar_modified: u64) -> Self {
+        let mut entry = Self {
+            sidecar_modified,
+            info,
+            search_text: String::new(),
+        };
+        entry.update_search_text();
+        entry
+    }
+
+    fn update_search_text(&mut self) {
+        self.search_text = format!(
+            "{}\n{}\n{}\n{}",
+            self.info.prompt.as_deref().unwrap_or(""),
+            self.info.negative_prompt.as_deref().unwrap_or(""),
+            self.info.tags.join(" "),
+            self.info.note.as_deref().unwrap_or("")
+        )
+        .to_lowercase();
+    }
+}
+
+/// Persistent gallery catalog stored at `<outputs>/.index/gallery.json`, keyed by media path
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryIndex {
+    pub version: u32,
+    pub outputs_folder: PathBuf,
+    pub entries: HashMap<String, GalleryIndexEntry>,
+    #[serde(skip)]
+    scanned_at: Option<Instant>,
+}
+
+impl GalleryIndex {
+    fn index_path(outputs_folder: &Path) -> PathBuf {
+        outputs_folder.join(".index").join("gallery.json")
+    }
+
+    /// Load the index for an outputs folder, starting empty if it is missing, unreadable or outdated
+    fn load(outputs_folder: &Path) -> Self {
+        let loaded = std::fs::read(Self::index_path(outputs_folder))
+            .ok()
+            .and_then(|data| serde_json::from_slice::<GalleryIndex>(&data).ok())
+            .filter(|index| index.version == INDEX_VERSION);
+        match loaded {
+            Some(mut index) => {
+                index.outputs_folder = outputs_folder.to_path_buf();
+                for entry in index.entries.values_mut() {
+                    entry.update_search_text();
+                }
+                index
+            }
+            None => Self {
+                version: INDEX_VERSION,
+                outputs_folder: outputs_folder.to_path_buf(),
+                entries: HashMap::new(),
+                scanned_at: None,
+            },
+        }
+    }
+
+    /// Write the index atomically (temp file + rename) so a crash never leaves it truncated
+    fn save(&self) -> Result<(), String> {
+        let path = Self::index_path(&self.outputs_folder);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_vec(self).map_err(|e| e.to_string())?;
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+
+    /// Re-read changed files, add new ones and drop deleted ones. Returns the paths of
+    /// (re)indexed videos, which still need probing, and whether anything changed
+    fn update(&mut self, files: Vec<(PathBuf, Option<String>)>) -> (Vec<String>, bool) {
+        let mut changed_videos = Vec::new();
+        let mut changed = false;
+        let mut seen: HashSet<String> = HashSet::with_capacity(files.len());
+
+        for (path, root) in files {
+            let key = path.to_string_lossy().to_string();
+            let Some((size, modified)) = file_stamp(&path) else {
+                continue;
+            };
+            let sidecar_modified = file_stamp(&path.with_extension("json"))
+                .map(|(_, modified)| modified)
+                .unwrap_or(0);
+            seen.insert(key.clone());
+
+            let unchanged = self.entries.get(&key).is_some_and(|entry| {
+                entry.info.size == size
+                    && entry.info.modified == modified
+                    && entry.sidecar_modified == sidecar_modified
+                    && entry.info.root == root
+            });
+            if unchanged {
+                continue;
+            }
+            if let Some(info) = load_media_info(&path, root) {
+                if info.media_type.as_deref() == Some("video") {
+                    changed_videos.push(key.clone());
+                }
+                self.entries.insert(key, GalleryIndexEntry::new(info, sidecar_modified));
+                changed = true;
+            }
+        }
+
+        let before = self.entries.len();
+        self.entries.retain(|key, _| seen.contains(key));
+        changed |= self.entries.len() != before;
+        (changed_videos, changed)
+    }
+}
+
+/// (size, modified ms) of a file
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    Some((metadata.len(), modified))
+}
+
+/// Bring the gallery index up to date with the outputs folder and gallery roots.
+/// Only files whose size, mtime or sidecar changed are re-read; the scan itself runs on a
+/// blocking thread so large galleries don't stall the async runtime
+pub(crate) async fn refresh_gallery_index(
+    state: &AppState,
+    force: bool,
+) -> Result<tokio::sync::MutexGuard<'_, Option<GalleryIndex>>, String> {
+    let guard = state.gallery_index.lock().await;
+    refresh_locked(state, guard, force).await
+}
+
+/// `refresh_gallery_index` with the index lock already held
+async fn refresh_locked<'a>(
+    state: &'a AppState,
+    mut guard: tokio::sync::MutexGuard<'a, Option<GalleryIndex>>,
+    force: bool,
+) -> Result<tokio::sync::MutexGuard<'a, Option<GalleryIndex>>, String> {
+    let outputs_folder = state::get_outputs_folder(state);
+    let roots = state.gallery_roots.lock().unwrap().clone();
+
+    let index = match guard.take() {
+        Some(index) if index.outputs_folder == outputs_folder => {
+            if !force && index.scanned_at.is_some_and(|t| t.elapsed() < RESCAN_INTERVAL) {
+                *guard = Some(index);
+                return Ok(guard);
+            }
+            index
+        }
+        _ => {
+            let folder = outputs_folder.clone();
+            tokio::task::spawn_blocking(move || GalleryIndex::load(&folder))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    let folder = outputs_folder.clone();
+    let (mut index, changed_videos, mut changed) = tokio::task::spawn_blocking(move || {
+        let mut index = index;
+        let files = collect_gallery_files(&folder, &roots);
+        let (changed_videos, changed) = index.update(files);
+        (index, changed_videos, changed)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Videos are probed only when FFmpeg is available
+    if let Ok(ffmpeg_path) = resolve_ffmpeg_path(state) {
+        for key in changed_videos {
+            if let Some(entry) = index.entries.get_mut(&key) {
+                apply_video_details(&mut entry.info, &ffmpeg_path, &outputs_folder).await;
+                changed = true;
+            }
+        }
+    }
+
+    index.scanned_at = Some(Instant::now());
+    let index = if changed {
+        // Best effort: the index is rebuilt from the folders if it can't be saved
+        tokio::task::spawn_blocking(move || {
+            let _ = index.save();
+            index
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    } else {
+        index
+    };
+    *guard = Some(index);
+    Ok(guard)
+}
+
+/// Make the next refresh rescan the folders, e.g. after the gallery roots changed
+pub(crate) async fn invalidate_gallery_index(state: &AppState) {
+    if let Some(index) = state.gallery_index.lock().await.as_mut() {
+        index.scanned_at = None;
+    }
+}
+
+/// Apply changes reported by the file watcher to the loaded index instead of rescanning.
+/// `changed` are added or modified files with their gallery root. Returns the fresh gallery
+/// info of the changed files
+pub(crate) async fn apply_gallery_changes(
+    state: &AppState,
+    changed: Vec<(PathBuf, Option<String>)>,
+    removed: &[PathBuf],
+) -> Vec<GeneratedImageInfo> {
+    let outputs_folder = state::get_outputs_folder(state);
+    let loaded = tokio::task::spawn_blocking(move || {
+        changed
+            .into_iter()
+            .filter_map(|(path, root)| {
+                let sidecar_modified = file_stamp(&path.with_extension("json"))
+                    .map(|(_, modified)| modified)
+                    .unwrap_or(0);
+                load_media_info(&path, root).map(|info| (info, sidecar_modified))
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .unwrap_or_default();
+
+    let mut entries = Vec::with_capacity(loaded.len());
+    let ffmpeg_path = resolve_ffmpeg_path(state).ok();
+    for (mut info, sidecar_modified) in loaded {
+        if let (Some(ffmpeg_path), Some("video")) = (&ffmpeg_path, info.media_type.as_deref()) {
+            apply_video_details(&mut info, ffmpeg_path, &outputs_folder).await;
+        }
+        entries.push(GalleryIndexEntry::new(info, sidecar_modified));
+    }
+    let infos: Vec<GeneratedImageInfo> = entries.iter().map(|entry| entry.info.clone()).collect();
+
+    let mut guard = state.gallery_index.lock().await;
+    // An index that isn't loaded (or belongs to another folder) is rebuilt by the next search
+    let Some(mut index) = guard.take().filter(|index| index.outputs_folder == outputs_folder) else {
+        return infos;
+    };
+    for path in removed {
+        index.entries.remove(&path.to_string_lossy().to_string());
+    }
+    for entry in entries {
+        index.entries.insert(entry.info.path.clone(), entry);
+    }
+    index.scanned_at = Some(Instant::now());
+    // Best effort: the index is rebuilt from the folders if it can't be saved
+    let index = tokio::task::spawn_blocking(move || {
+        let _ = index.save();
+        index
+    })
+    .await;
+    if let Ok(index) = index {
+        *guard = Some(index);
+    }
+    infos
+}
+
+/// Payload of `gallery:changed`
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryChangedEvent {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    /// Gallery info of the added and modified items
+    pub items: Vec<GeneratedImageInfo>,
+}
+
+/// Rescan the gallery and report how it differs from the index before the scan. Used by the
+/// file watcher for folder-level changes (a subfolder moved in, renamed or deleted) that
+/// don't map to single files
+pub(crate) async fn rescan_gallery_changes(state: &AppState) -> Result<GalleryChangedEvent, String> {
+    let stamp = |entry: &GalleryIndexEntry| (entry.info.size, entry.info.modified, entry.sidecar_modified);
+    let outputs_folder = state::get_outputs_folder(state);
+    let mut guard = state.gallery_index.lock().await;
+
+    // Snapshot the index as it is, loading the saved one if needed, without scanning; the
+    // lock is held until the rescan so nothing else absorbs the change in between
+    if guard.as_ref().is_none_or(|index| index.outputs_folder != outputs_folder) {
+        let folder = outputs_folder.clone();
+        let index = tokio::task::spawn_blocking(move || GalleryIndex::load(&folder))
+            .await
+            .map_err(|e| e.to_string())?;
+        *guard = Some(index);
+    }
+    let before: HashMap<String, (u64, u64, u64)> = guard
+        .as_ref()
+        .map(|index| index.entries.iter().map(|(key, entry)| (key.clone(), stamp(entry))).collect())
+        .unwrap_or_default();
+
+    let guard = refresh_locked(state, guard, true).await?;
+    let mut changes = GalleryChangedEvent::default();
+    let Some(index) = guard.as_ref() else {
+        return Ok(changes);
+    };
+    for (key, entry) in &index.entries {
+        match before.get(key) {
+            None => changes.added.push(key.clone()),
+            Some(previous) if *previous != stamp(entry) => changes.modified.push(key.clone()),
+            _ => continue,
+        }
+        changes.items.push(entry.info.clone());
+    }
+    changes.removed = before
+        .into_keys()
+        .filter(|key| !index.entries.contains_key(key))
+        .collect();
+    Ok(changes)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GallerySearchRequest {
+    /// Whitespace-separated terms, all of which must appear in the prompt, negative prompt,
+    /// tags or note
+    pub query: Option<String>,
+    pub group_id: Option<String>,
+    pub sampling_method: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Modified time range in milliseconds since the epoch, inclusive
+    pub date_from: Option<u64>,
+    pub date_to: Option<u64>,
+    /// "image" or "video"
+    pub media_type: Option<String>,
+    pub device_type: Option<String>,
+    /// Generation type, e.g. "generate", "edit", "video"
+    pub gen_type: Option<String>,
+    /// "modified" (default), "name", "size", "width", "height", "steps", "seed" or "rating"
+    pub sort: Option<String>,
+    /// "desc" (default) or "asc"
+    pub order: Option<String>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    /// Rating, favorite and tag filters
+    #[serde(flatten)]
+    pub annotations: GalleryAnnotationFilter,
+    /// Rescan the folders even if the last scan is recent
+    pub refresh: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GallerySearchResult {
+    pub items: Vec<GeneratedImageInfo>,
+    /// Pass back as `cursor` to get the next page; None on the last page
+    pub next_cursor: Option<String>,
+    /// Number of items matching the filters
+    pub total: usize,
+}
+
+/// Items without the sorted field get `Number(None)`, which sorts before every value.
+/// Numbers stay integers so they survive the cursor's JSON round trip exactly
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Number(Option<i128>),
+    Text(String),
+}
+
+/// Position after the last item of a page: its sort key with the path as tie-breaker
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    key: SortKey,
+    path: String,
+}
+
+fn sort_key(info: &GeneratedImageInfo, sort: &str) -> SortKey {
+    let number = |value: Option<i128>| SortKey::Number(value);
+    match sort {
+        "name" => SortKey::Text(info.name.to_lowercase()),
+        "size" => number(Some(info.size.into())),
+        "width" => number(info.width.map(i128::from)),
+        "height" => number(info.height.map(i128::from)),
+        "steps" => number(info.steps.map(i128::from)),
+        "seed" => number(info.seed.map(i128::from)),
+        "rating" => number(Some(info.rating.unwrap_or(0).into())),
+        _ => number(Some(info.modified.into())),
+    }
+}
+
+fn matches(entry: &GalleryIndexEntry, request: &GallerySearchRequest, terms: &[String]) -> bool {
+    let info = &entry.info;
+    let eq = |filter: &Option<String>, value: &Option<String>| {
+        filter.as_ref().is_none_or(|f| value.as_deref() == Some(f.as_str()))
+    };
+    terms.iter().all(|term| entry.search_text.contains(term.as_str()))
+        && eq(&request.group_id, &info.group_id)
+        && eq(&request.sampling_method, &info.sampling_method)
+        && eq(&request.media_type, &info.media_type)
+        && eq(&request.device_type, &info.device_type)
+        && eq(&request.gen_type, &info.gen_type)
+        && request.width.is_none_or(|w| info.width == Some(w))
+        && request.height.is_none_or(|h| info.height == Some(h))
+        && request.date_from.is_none_or(|from| info.modified >= from)
+        && request.date_to.is_none_or(|to| info.modified <= to)
+        && request.annotations.matches(info)
+}
+
+/// Search the gallery index with filters, sorting and cursor pagination
+#[tauri::command]
+pub async fn gallery_search(
+    value: GallerySearchRequest,
+    state: State<'_, AppState>,
+) -> Result<GallerySearchResult, String> {
+    let sort = value.sort.clone().unwrap_or_else(|| "modified".to_string());
+    let descending = value.order.as_deref() != Some("asc");
+    let limit = value.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let cursor: Option<Cursor> = match &value.cursor {
+        Some(cursor) => Some(
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(cursor)
+                .ok()
+                .and_then(|data| serde_json::from_slice(&data).ok())
+                .ok_or("Invalid cursor")?,
+        ),
+        None => None,
+    };
+    let terms: Vec<String> = value
+        .query
+        .as_deref()
+        .unwrap_or("")
+        .split_whitespace()
+        .map(|term| term.to_lowercase())
+        .collect();
+
+    let index = refresh_gallery_index(&state, value.refresh).await?;
+    let Some(index) = index.as_ref() else {
+        return Ok(GallerySearchResult {
+            items: Vec::new(),
+            next_cursor: None,
+            total: 0,
+        });
+    };
+
+    let compare = |a_key: &SortKey, a_path: &str, b_key: &SortKey, b_path: &str| {
+        let ordering = a_key.cmp(b_key).then_with(|| a_path.cmp(b_path));
+        if descending { ordering.reverse() } else { ordering }
+    };
+
+    let mut matched: Vec<(SortKey, &str, &GalleryIndexEntry)> = index
+        .entries
+        .iter()
+        .filter(|(_, entry)| matches(entry, &value, &terms))
+        .map(|(path, entry)| (sort_key(&entry.info, &sort), path.as_str(), entry))
+        .collect();
+    let total = matched.len();
+    if let Some(cursor) = &cursor {
+        matched.retain(|(key, path, _)| compare(key, path, &cursor.key, &cursor.path) == Ordering::Greater);
+    }
+    matched.sort_by(|a, b| compare(&a.0, a.1, &b.0, b.1));
+
+    let has_more = matched.len() > limit;
+    matched.truncate(limit);
+    let next_cursor = match matched.last() {
+        Some((key, path, _)) if has_more => {
+            let cursor = Cursor {
+                key: key.clone(),
+                path: path.to_string(),
+            };
+            let data = serde_json::to_vec(&cursor).map_err(|e| e.to_string())?;
+            Some(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data))
+        }
+        _ => None,
+    };
+
+    Ok(GallerySearchResult {
+        items: matched.into_iter().map(|(_, _, entry)| entry.info.clone()).collect(),
+        next_cursor,
+        total,
+    })
+}
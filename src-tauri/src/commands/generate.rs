@@ -1,12 +1,19 @@
+use crate::imaging;
 use crate::state::{self, AppState};
 use base64::Engine;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter, State};
 use tokio::io::{AsyncBufReadExt, BufReader};
 
+use super::embeddings::{list_embedding_files, referenced_embeddings, resolve_embeddings_folder};
+use super::generated_images::{annotate_sidecar, EngineInfo, ModelFileInfo};
+use super::model_groups::{apply_group_defaults, load_model_groups};
+use super::sdcpp::get_sdcpp_version;
+
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
@@ -26,15 +33,15 @@ pub struct CliOutput {
 }
 
 #[cfg(target_os = "windows")]
-fn configure_command(cmd: &mut tokio::process::Command) {
+pub(crate) fn configure_command(cmd: &mut tokio::process::Command) {
     use std::os::windows::process::CommandExt;
     cmd.as_std_mut().creation_flags(CREATE_NO_WINDOW);
 }
 
 #[cfg(not(target_os = "windows"))]
-fn configure_command(_cmd: &mut tokio::process::Command) {}
+pub(crate) fn configure_command(_cmd: &mut tokio::process::Command) {}
 
-fn resolve_sdcpp_executable(device_folder: &Path) -> Option<std::path::PathBuf> {
+pub(crate) fn resolve_sdcpp_executable(device_folder: &Path) -> Option<std::path::PathBuf> {
     let candidates: &[&str] = if cfg!(target_os = "windows") {
         &["sd-cli.exe", "sd.exe", "sd_server.exe", "sd-server.exe"]
     } else {
@@ -86,7 +93,7 @@ fn resolve_model_path_with_fallback(model_path: &str, primary_base_folder: &str)
     state::resolve_model_path(model_path, primary_base_folder)
 }
 
-fn resolve_generate_model_path(
+pub(crate) fn resolve_generate_model_path(
     model_path: &str,
     weights_folder: &str,
     group_folder: Option<&str>,
@@ -116,6 +123,10 @@ pub async fn generate_start(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<serde_json::Value, String> {
+    // Fill omitted parameters from the model group's defaults
+    let request = value.clone();
+    let value = apply_group_defaults(value, &state);
+
     let device_type = value["deviceType"].as_str().unwrap_or("cpu");
     let prompt = value["prompt"].as_str().unwrap_or("");
 
@@ -151,23 +162,27 @@ pub async fn generate_start(
         .as_millis();
     let output_path = Path::new(&outputs_folder).join(format!("generated_{}.png", timestamp));
 
+    // PhotoMaker reads ID images from a directory, so copy them into a per-job one
+    let pm_id_images_dir = prepare_photomaker_id_images(&value, Path::new(&outputs_folder), timestamp)?;
+
     // Build CLI arguments
-    let args = build_generate_args(&value, &state, &output_path)?;
-
-    let command_line = format!(
-        "{} {}",
-        exe_path.display(),
-        args.iter()
-            .map(|arg| {
-                if arg.contains(' ') {
-                    format!("\"{}\"", arg)
-                } else {
-                    arg.clone()
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
+    let args = build_generate_args(
+        &value,
+        &state,
+        &output_path,
+        pm_id_images_dir.as_ref().map(|d| d.path()),
+    )?;
+
+    let command_line = format!("{} {}", exe_path.display(), quote_args(&args));
+    if value["dryRun"].as_bool() == Some(true) {
+        return Ok(serde_json::json!({
+            "success": true,
+            "dryRun": true,
+            "args": args,
+            "commandLine": command_line,
+            "effectiveParams": &value,
+        }));
+    }
     let _ = app.emit(
         "generate:cli-output",
         CliOutput {
@@ -398,6 +413,10 @@ pub async fn generate_start(
         let b64 = base64::engine::general_purpose::STANDARD.encode(&image_data);
         let data_url = format!("data:image/png;base64,{}", b64);
 
+        let models = collect_model_files(&args).await;
+        let engine = describe_engine(device_type, &device_folder, &exe_path).await;
+        let group_name = lookup_group_name(&state, value["groupId"].as_str());
+
         // Save metadata
         let metadata_path = output_path.with_extension("json");
         let metadata = serde_json::json!({
@@ -412,9 +431,27 @@ pub async fn generate_start(
             "scheduler": value.get("scheduler"),
             "deviceType": device_type,
             "groupId": value.get("groupId"),
+            "groupName": group_name,
+            "modelPath": primary_model_path(&models),
+            "vaeModelPath": models.get("vaeModel").map(|m| &m.path),
+            "llmModelPath": models.get("llmModel").map(|m| &m.path),
+            "models": models,
+            "engine": engine,
+            "refImages": collect_reference_images(&args),
+            "photoMaker": value.get("idImages").map(|id_images| serde_json::json!({
+                "idImages": id_images,
+                "styleStrength": value.get("pmStyleStrength"),
+            })),
+            "embeddings": prompt_embeddings(&value, &state),
+            "circularPadding": value.get("circularPadding"),
+            "commandLine": command_line,
+            "threads": value.get("threads"),
+            "batchCount": value.get("batchCount"),
             "type": "generate",
             "mediaType": "image",
             "duration": duration,
+            "request": &request,
+            "effectiveParams": &value,
             "generatedAt": chrono::Utc::now().to_rfc3339(),
         });
         let _ = tokio::fs::write(
@@ -423,10 +460,35 @@ pub async fn generate_start(
         )
         .await;
 
+        // Seamless textures get a tiled preview for seam inspection
+        let tile_preview = match circular_padding_flag(&value) {
+            Some(_) => {
+                let mode = value["circularPadding"].as_str().unwrap_or_default();
+                match write_tile_preview(&output_path, mode, prompt) {
+                    Ok(preview_path) => Some(preview_path.to_string_lossy().to_string()),
+                    Err(e) => {
+                        let _ = app.emit(
+                            "generate:cli-output",
+                            CliOutput {
+                                output_type: "stderr".to_string(),
+                                text: format!("[tile preview] {}", e),
+                            },
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        if let Some(preview_path) = &tile_preview {
+            annotate_sidecar(&output_path, serde_json::json!({ "tilePreview": preview_path }));
+        }
+
         Ok(serde_json::json!({
             "success": true,
             "image": data_url,
             "imagePath": output_path.to_string_lossy(),
+            "tilePreviewPath": tile_preview,
             "duration": duration
         }))
     } else {
@@ -437,6 +499,286 @@ pub async fn generate_start(
     }
 }
 
+/// Join CLI arguments for display, quoting those that contain spaces
+pub(crate) fn quote_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.contains(' ') {
+                format!("\"{}\"", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Image formats accepted as reference images
+const REFERENCE_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "bmp"];
+
+/// CLI flags that carry a model file, mapped to the model group slot they come from
+const MODEL_ARG_SLOTS: &[(&str, &str)] = &[
+    ("-m", "sdModel"),
+    ("--diffusion-model", "diffusionModel"),
+    ("--high-noise-model", "highNoiseSdModel"),
+    ("--high-noise-diffusion-model", "highNoiseSdModel"),
+    ("--vae", "vaeModel"),
+    ("--llm", "llmModel"),
+    ("--clip_l", "clipLModel"),
+    ("--t5xxl", "t5xxlModel"),
+    ("--clip-vision", "clipVisionModel"),
+    ("--taesd", "taesdModel"),
+    ("--llm_vision", "llmVisionModel"),
+    ("--photo-maker", "photoMakerModel"),
+];
+
+/// Collect the resolved model files passed to sd.cpp, keyed by model group slot
+pub(crate) async fn collect_model_files(args: &[String]) -> BTreeMap<String, ModelFileInfo> {
+    let mut models = BTreeMap::new();
+    for pair in args.windows(2) {
+        let Some((_, slot)) = MODEL_ARG_SLOTS.iter().find(|(flag, _)| *flag == pair[0]) else {
+            continue;
+        };
+        let path = pair[1].clone();
+        let hash_path = path.clone();
+        let (size, sample_hash) = tokio::task::spawn_blocking(move || sample_file_hash(Path::new(&hash_path)))
+            .await
+            .ok()
+            .flatten()
+            .map(|(size, hash)| (Some(size), Some(hash)))
+            .unwrap_or((None, None));
+        models.insert(slot.to_string(), ModelFileInfo { path, size, sample_hash });
+    }
+    models
+}
+
+/// The main model of a run: the standalone diffusion model if present, otherwise the full SD model
+pub(crate) fn primary_model_path(models: &BTreeMap<String, ModelFileInfo>) -> Option<&str> {
+    models
+        .get("diffusionModel")
+        .or_else(|| models.get("sdModel"))
+        .map(|m| m.path.as_str())
+}
+
+/// MD5 over the file size plus three 1 MiB samples (head, middle, tail).
+/// Full hashes of multi-GB weights would stall every generation; the sample is enough to notice
+/// a replaced file, but files that only differ outside the sampled regions hash the same.
+pub(crate) fn sample_file_hash(path: &Path) -> Option<(u64, String)> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    const SAMPLE_SIZE: u64 = 1024 * 1024;
+    let mut file = std::fs::File::open(path).ok()?;
+    let size = file.metadata().ok()?.len();
+
+    let mut ctx = md5::Context::new();
+    ctx.consume(size.to_le_bytes());
+    let mut buf = vec![0u8; SAMPLE_SIZE as usize];
+    for offset in [0, size.saturating_sub(SAMPLE_SIZE) / 2, size.saturating_sub(SAMPLE_SIZE)] {
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        let read = file.by_ref().take(SAMPLE_SIZE).read(&mut buf).ok()?;
+        ctx.consume(&buf[..read]);
+    }
+    Some((size, format!("{:x}", ctx.compute())))
+}
+
+/// Describe the engine used for a run (device, CPU variant and sd.cpp commit)
+pub(crate) async fn describe_engine(device_type: &str, device_folder: &Path, exe_path: &Path) -> EngineInfo {
+    let (device, variant) = match device_type.split_once('-') {
+        Some((device, variant)) => (device.to_string(), Some(variant.to_string())),
+        None => (device_type.to_string(), None),
+    };
+    EngineInfo {
+        device,
+        variant,
+        version: get_sdcpp_version(device_folder).await,
+        executable: Some(exe_path.to_string_lossy().to_string()),
+    }
+}
+
+/// Resolve `refImages` to existing image files.
+/// Relative paths are looked up in the outputs folder so gallery items can be referenced by name.
+fn resolve_reference_images(
+    params: &serde_json::Value,
+    state: &State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let Some(refs) = params["refImages"].as_array() else {
+        return Ok(Vec::new());
+    };
+
+    let outputs_folder = state
+        .outputs_folder
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| state::get_default_outputs_folder().to_string_lossy().to_string());
+
+    let mut resolved = Vec::new();
+    let mut invalid = Vec::new();
+    for ref_image in refs.iter().filter_map(|r| r.as_str()).filter(|r| !r.trim().is_empty()) {
+        let path = if Path::new(ref_image).is_absolute() {
+            PathBuf::from(ref_image)
+        } else {
+            Path::new(&outputs_folder).join(ref_image)
+        };
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if !path.is_file() || !REFERENCE_IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            invalid.push(ref_image.to_string());
+            continue;
+        }
+        resolved.push(dunce::simplified(&path).to_string_lossy().to_string());
+    }
+
+    if !invalid.is_empty() {
+        return Err(format!("参考图不存在或格式不支持：{}", invalid.join(", ")));
+    }
+    Ok(resolved)
+}
+
+/// Map `circularPadding` ("both" / "x" / "y") to the matching sd.cpp flag
+fn circular_padding_flag(params: &serde_json::Value) -> Option<&'static str> {
+    match params["circularPadding"].as_str()? {
+        "both" => Some("--circular"),
+        "x" => Some("--circularx"),
+        "y" => Some("--circulary"),
+        _ => None,
+    }
+}
+
+/// Render a 2x2 tiled copy of a seamless texture next to it so seams can be inspected
+/// in the gallery. Returns the preview path.
+fn write_tile_preview(image_path: &Path, circular_padding: &str, prompt: &str) -> Result<PathBuf, String> {
+    let texture = imaging::read_png(image_path)?;
+    let tiled = texture.tile(2, 2);
+
+    let stem = image_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let preview_path = image_path.with_file_name(format!("{}_tiled.png", stem));
+    imaging::write_png(&preview_path, &tiled)?;
+
+    let metadata = serde_json::json!({
+        "prompt": prompt,
+        "width": tiled.width,
+        "height": tiled.height,
+        "type": "tile-preview",
+        "mediaType": "image",
+        "sourceImage": image_path.to_string_lossy(),
+        "circularPadding": circular_padding,
+        "generatedAt": chrono::Utc::now().to_rfc3339(),
+    });
+    std::fs::write(
+        preview_path.with_extension("json"),
+        serde_json::to_string_pretty(&metadata).unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(preview_path)
+}
+
+/// Embeddings folder for `--embd-dir`, when it exists
+pub(crate) fn embeddings_dir_arg(state: &AppState) -> Option<String> {
+    let folder = resolve_embeddings_folder(state);
+    folder.is_dir().then(|| folder.to_string_lossy().to_string())
+}
+
+/// Embeddings referenced by the prompt and negative prompt, for metadata
+pub(crate) fn prompt_embeddings(params: &serde_json::Value, state: &AppState) -> serde_json::Value {
+    let names = list_embedding_files(&resolve_embeddings_folder(state));
+    if names.is_empty() {
+        return serde_json::Value::Null;
+    }
+    let prompt = referenced_embeddings(params["prompt"].as_str().unwrap_or(""), &names);
+    let negative = referenced_embeddings(params["negativePrompt"].as_str().unwrap_or(""), &names);
+    if prompt.is_empty() && negative.is_empty() {
+        return serde_json::Value::Null;
+    }
+    serde_json::json!({ "prompt": prompt, "negativePrompt": negative })
+}
+
+/// A per-job directory removed when dropped
+pub(crate) struct JobTempDir(PathBuf);
+
+impl JobTempDir {
+    pub(crate) fn create(path: PathBuf) -> Result<Self, String> {
+        std::fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+        Ok(Self(path))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for JobTempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Copy `idImages` into `<outputs>/.photomaker/<timestamp>` for `--pm-id-images-dir`
+fn prepare_photomaker_id_images(
+    params: &serde_json::Value,
+    outputs_folder: &Path,
+    timestamp: u128,
+) -> Result<Option<JobTempDir>, String> {
+    let id_images: Vec<&str> = params["idImages"]
+        .as_array()
+        .map(|images| {
+            images
+                .iter()
+                .filter_map(|i| i.as_str())
+                .filter(|i| !i.trim().is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    if id_images.is_empty() {
+        return Ok(None);
+    }
+
+    let missing: Vec<&str> = id_images
+        .iter()
+        .copied()
+        .filter(|i| !Path::new(i).is_file())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("PhotoMaker 身份图不存在：{}", missing.join(", ")));
+    }
+
+    let dir = JobTempDir::create(outputs_folder.join(".photomaker").join(timestamp.to_string()))?;
+    for (index, image) in id_images.iter().enumerate() {
+        let src = Path::new(image);
+        let ext = src.extension().and_then(|e| e.to_str()).unwrap_or("png");
+        let dest = dir.path().join(format!("id_{:02}.{}", index, ext));
+        std::fs::copy(src, &dest).map_err(|e| e.to_string())?;
+    }
+    Ok(Some(dir))
+}
+
+/// Reference image paths passed to sd.cpp via `-r`
+pub(crate) fn collect_reference_images(args: &[String]) -> Vec<String> {
+    args.windows(2)
+        .filter(|pair| pair[0] == "-r")
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+/// Look up a model group's display name by ID
+pub(crate) fn lookup_group_name(state: &State<'_, AppState>, group_id: Option<&str>) -> Option<String> {
+    let group_id = group_id.filter(|id| !id.trim().is_empty())?;
+    let weights_folder = state.weights_folder.lock().unwrap().clone();
+    let groups_path = state::get_model_groups_path(weights_folder.as_deref());
+    load_model_groups(&groups_path)
+        .ok()?
+        .into_iter()
+        .find(|g| g.id == group_id)
+        .map(|g| g.name)
+}
+
 /// Cancel image generation
 #[tauri::command]
 pub async fn generate_cancel(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
@@ -452,6 +794,7 @@ fn build_generate_args(
     params: &serde_json::Value,
     state: &State<'_, AppState>,
     output_path: &Path,
+    pm_id_images_dir: Option<&Path>,
 ) -> Result<Vec<String>, String> {
     let mut args = Vec::new();
 
@@ -560,6 +903,39 @@ fn build_generate_args(
             args.push(resolved);
         }
     }
+    if let Some(taesd) = params["taesdModel"].as_str() {
+        if !taesd.is_empty() {
+            let resolved = resolve_generate_model_path(
+                taesd,
+                &weights_folder,
+                group_folder_from_params.as_deref(),
+            );
+            args.push("--taesd".to_string());
+            args.push(resolved);
+        }
+    }
+    if let Some(llm_vision) = params["llmVisionModel"].as_str() {
+        if !llm_vision.is_empty() {
+            let resolved = resolve_generate_model_path(
+                llm_vision,
+                &weights_folder,
+                group_folder_from_params.as_deref(),
+            );
+            args.push("--llm_vision".to_string());
+            args.push(resolved);
+        }
+    }
+    if let Some(photo_maker) = params["photoMakerModel"].as_str() {
+        if !photo_maker.is_empty() {
+            let resolved = resolve_generate_model_path(
+                photo_maker,
+                &weights_folder,
+                group_folder_from_params.as_deref(),
+            );
+            args.push("--photo-maker".to_string());
+            args.push(resolved);
+        }
+    }
 
     // Model paths
     if !model_paths_injected {
@@ -627,6 +1003,24 @@ fn build_generate_args(
                         args.push(resolve_path(clip_vision));
                     }
                 }
+                if let Some(taesd) = group["taesdModel"].as_str() {
+                    if !taesd.is_empty() {
+                        args.push("--taesd".to_string());
+                        args.push(resolve_path(taesd));
+                    }
+                }
+                if let Some(llm_vision) = group["llmVisionModel"].as_str() {
+                    if !llm_vision.is_empty() {
+                        args.push("--llm_vision".to_string());
+                        args.push(resolve_path(llm_vision));
+                    }
+                }
+                if let Some(photo_maker) = group["photoMakerModel"].as_str() {
+                    if !photo_maker.is_empty() {
+                        args.push("--photo-maker".to_string());
+                        args.push(resolve_path(photo_maker));
+                    }
+                }
             }
         }
     }
@@ -661,6 +1055,12 @@ fn build_generate_args(
         }
     }
 
+    // Textual inversion embeddings
+    if let Some(embd_dir) = embeddings_dir_arg(state) {
+        args.push("--embd-dir".to_string());
+        args.push(embd_dir);
+    }
+
     // Image dimensions
     if let Some(w) = params["width"].as_u64() {
         args.push("-W".to_string());
@@ -770,6 +1170,10 @@ fn build_generate_args(
         args.push("--preview-interval".to_string());
         args.push(preview_interval.to_string());
     }
+    // TAESD 仅用于预览时，最终图像仍由完整 VAE 解码
+    if params["taesdPreviewOnly"].as_bool() == Some(true) {
+        args.push("--taesd-preview-only".to_string());
+    }
 
     // Input image for img2img
     if let Some(input) = params["inputImage"].as_str() {
@@ -779,6 +1183,41 @@ fn build_generate_args(
         }
     }
 
+    // Inpainting mask (white = repaint)
+    if let Some(mask) = params["maskImage"].as_str() {
+        if !mask.is_empty() {
+            args.push("--mask".to_string());
+            args.push(mask.to_string());
+        }
+    }
+
+    // Reference images for instruction-based editing (Flux Kontext / Qwen-Image-Edit)
+    for ref_image in resolve_reference_images(params, state)? {
+        args.push("-r".to_string());
+        args.push(ref_image);
+    }
+
+    // Circular padding for seamless tileable output
+    if let Some(flag) = circular_padding_flag(params) {
+        args.push(flag.to_string());
+    }
+
+    // PhotoMaker identity images and style strength
+    if let Some(dir) = pm_id_images_dir {
+        args.push("--pm-id-images-dir".to_string());
+        args.push(dir.to_string_lossy().to_string());
+    }
+    if let Some(style_strength) = params["pmStyleStrength"].as_f64() {
+        args.push("--pm-style-strength".to_string());
+        args.push(style_strength.to_string());
+    }
+
+    // Denoising strength for img2img / inpainting
+    if let Some(strength) = params["strength"].as_f64() {
+        args.push("--strength".to_string());
+        args.push(strength.to_string());
+    }
+
     // Output path
     args.push("-o".to_string());
     args.push(output_path.to_string_lossy().to_string());
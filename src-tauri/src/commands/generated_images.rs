@@ -1,11 +1,40 @@
-use crate::state;
+use crate::state::{self, AppState, GalleryRoot};
 use base64::Engine;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::Write;
-use std::path::Path;
-use tauri::AppHandle;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
 use tauri_plugin_dialog::DialogExt;
 
+use super::ffmpeg::{probe_video, run_ffmpeg, VideoProbe};
+use super::gallery_index::{invalidate_gallery_index, refresh_gallery_index};
+use super::generate::{generate_start, sample_file_hash};
+use super::model_groups::load_model_groups;
+use super::outpaint::generate_outpaint;
+use super::thumbnails::remove_thumbnails;
+use super::trash::{move_to_trash, TrashKind};
+use super::video_generate::generate_video_start;
+
+/// Request fields recoverable from sidecars written before the full request was recorded
+const LEGACY_REQUEST_KEYS: &[&str] = &[
+    "prompt",
+    "negativePrompt",
+    "steps",
+    "cfgScale",
+    "width",
+    "height",
+    "seed",
+    "samplingMethod",
+    "scheduler",
+    "deviceType",
+    "groupId",
+    "mode",
+];
+
+/// Request fields that point at input files
+const INPUT_FILE_KEYS: &[&str] = &["inputImage", "initImage", "endImage", "maskImage", "sourceImage"];
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GeneratedImageInfo {
@@ -13,6 +42,10 @@ pub struct GeneratedImageInfo {
     pub path: String,
     pub size: u64,
     pub modified: u64,
+    /// Extra gallery root the item was found in; None for the outputs folder
+    pub root: Option<String>,
+    /// Items from extra gallery roots can't be deleted or overwritten
+    pub read_only: bool,
     pub width: Option<u32>,
     pub height: Option<u32>,
     #[serde(rename = "type")]
@@ -38,121 +71,440 @@ pub struct GeneratedImageInfo {
     pub preview_image: Option<String>,
     pub verbose: Option<bool>,
     pub command_line: Option<String>,
+    pub models: Option<BTreeMap<String, ModelFileInfo>>,
+    pub engine: Option<EngineInfo>,
+    pub regenerated_from: Option<String>,
+    pub source_image: Option<String>,
+    pub source_video: Option<String>,
+    pub source_images: Option<Vec<String>>,
+    pub source_videos: Option<Vec<String>>,
+    pub ref_images: Option<Vec<String>>,
+    pub photo_maker: Option<serde_json::Value>,
+    pub circular_padding: Option<String>,
+    pub tile_preview: Option<String>,
+    pub embeddings: Option<serde_json::Value>,
+    pub video_frames: Option<u32>,
+    pub fps: Option<f64>,
+    /// Playback length of a video in seconds (`duration` is the generation time)
+    pub video_duration: Option<f64>,
+    pub video_codec: Option<String>,
+    /// Cached poster frame for videos
+    pub poster: Option<String>,
+    /// 1-5 stars
+    pub rating: Option<u8>,
+    pub favorite: bool,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
     pub generated_at: Option<String>,
     pub duration: Option<u64>,
 }
 
-/// List all generated images/videos
+/// Curation filters shared by the gallery list and search
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GalleryAnnotationFilter {
+    pub min_rating: Option<u8>,
+    pub favorite: Option<bool>,
+    /// Items must carry all of these tags
+    pub tags: Vec<String>,
+}
+
+impl GalleryAnnotationFilter {
+    pub(crate) fn matches(&self, info: &GeneratedImageInfo) -> bool {
+        self.min_rating.is_none_or(|min| info.rating.unwrap_or(0) >= min)
+            && self.favorite.is_none_or(|favorite| info.favorite == favorite)
+            && self
+                .tags
+                .iter()
+                .all(|tag| info.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
+/// A tag and the number of gallery items carrying it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryTagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Rating/favorite/tag/note changes applied to a selection of gallery items.
+/// Unset fields are left unchanged
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GalleryAnnotationUpdate {
+    pub paths: Vec<String>,
+    /// 1-5; 0 clears the rating
+    pub rating: Option<u8>,
+    pub favorite: Option<bool>,
+    /// Replace all tags
+    pub tags: Option<Vec<String>>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+    /// An empty note clears it
+    pub note: Option<String>,
+}
+
+/// A model file used for a run, as recorded in the sidecar metadata
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelFileInfo {
+    pub path: String,
+    pub size: Option<u64>,
+    /// `sample_file_hash` of the file: MD5 over its size and three 1 MiB samples. Only meant
+    /// to notice a replaced file, not a content hash comparable with published checksums.
+    /// Older sidecars stored the same value as `hash`
+    #[serde(alias = "hash")]
+    pub sample_hash: Option<String>,
+}
+
+/// The sd.cpp engine used for a run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineInfo {
+    pub device: String,
+    pub variant: Option<String>,
+    pub version: Option<String>,
+    pub executable: Option<String>,
+}
+
+/// Media file extensions shown in the gallery
+pub(crate) const GALLERY_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "mp4", "webm", "avi"];
+
+/// Upper bound for gallery root scan depth
+const MAX_ROOT_DEPTH: u32 = 8;
+
+/// Get the outputs folder generated media is written to
+#[tauri::command]
+pub async fn generated_images_get_outputs_folder(state: State<'_, AppState>) -> Result<String, String> {
+    Ok(state::get_outputs_folder(&state).to_string_lossy().to_string())
+}
+
+/// Set the outputs folder, creating it if needed; an empty value restores the default
 #[tauri::command]
-pub async fn generated_images_list() -> Result<Vec<GeneratedImageInfo>, String> {
-    let outputs_folder = state::get_default_outputs_folder();
-    if !outputs_folder.is_dir() {
-        return Ok(vec![]);
+pub async fn generated_images_set_outputs_folder(
+    value: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let folder = if value.trim().is_empty() {
+        state::get_default_outputs_folder()
+    } else {
+        PathBuf::from(&value)
+    };
+    if std::fs::create_dir_all(&folder).is_err() || !folder.is_dir() {
+        return Ok(false);
     }
+    *state.outputs_folder.lock().unwrap() = Some(folder.to_string_lossy().to_string());
+    Ok(true)
+}
 
-    let mut images = Vec::new();
-    let entries = std::fs::read_dir(&outputs_folder).map_err(|e| e.to_string())?;
+/// Get the extra read-only gallery roots
+#[tauri::command]
+pub async fn generated_images_get_roots(state: State<'_, AppState>) -> Result<Vec<GalleryRoot>, String> {
+    Ok(state.gallery_roots.lock().unwrap().clone())
+}
 
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        let name = path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
+/// Replace the extra read-only gallery roots; folders that don't exist are rejected
+#[tauri::command]
+pub async fn generated_images_set_roots(
+    value: Vec<GalleryRoot>,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    if let Some(missing) = value.iter().find(|root| !Path::new(&root.path).is_dir()) {
+        return Err(format!("Folder not found: {}", missing.path));
+    }
+    let roots = value
+        .into_iter()
+        .map(|root| GalleryRoot {
+            depth: root.depth.min(MAX_ROOT_DEPTH),
+            ..root
+        })
+        .collect();
+    *state.gallery_roots.lock().unwrap() = roots;
+    invalidate_gallery_index(&state).await;
+    Ok(true)
+}
 
-        // Only process image/video files
-        let ext = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("")
-            .to_lowercase();
+/// List all generated images/videos in the outputs folder and the extra gallery roots,
+/// optionally filtered by rating, favorite and tags
+#[tauri::command]
+pub async fn generated_images_list(
+    value: Option<GalleryAnnotationFilter>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GeneratedImageInfo>, String> {
+    let filter = value.unwrap_or_default();
+    let index = refresh_gallery_index(&state, false).await?;
+    let mut images: Vec<GeneratedImageInfo> = index
+        .as_ref()
+        .map(|index| {
+            index
+                .entries
+                .values()
+                .filter(|entry| filter.matches(&entry.info))
+                .map(|entry| entry.info.clone())
+                .collect()
+        })
+        .unwrap_or_default();
 
-        if !["png", "jpg", "jpeg", "webp", "mp4", "avi"].contains(&ext.as_str()) {
+    // Sort by modified time descending
+    images.sort_by(|a, b| b.modified.cmp(&a.modified));
+
+    Ok(images)
+}
+
+/// Gallery media files in the outputs folder and the extra gallery roots, with the root each
+/// came from (None for the outputs folder)
+pub(crate) fn collect_gallery_files(outputs_folder: &Path, roots: &[GalleryRoot]) -> Vec<(PathBuf, Option<String>)> {
+    let mut files: Vec<(PathBuf, Option<String>)> = Vec::new();
+    if outputs_folder.is_dir() {
+        collect_media_files(outputs_folder, 0, &mut |path| files.push((path, None)));
+    }
+    let mut seen: std::collections::HashSet<PathBuf> = files.iter().map(|(path, _)| path.clone()).collect();
+    let outputs_canonical = dunce::canonicalize(outputs_folder).ok();
+    for root in roots {
+        let root_path = Path::new(&root.path);
+        // The outputs folder is already listed (and writable) on its own
+        if dunce::canonicalize(root_path).ok() == outputs_canonical && root.depth == 0 {
             continue;
         }
+        collect_media_files(root_path, root.depth.min(MAX_ROOT_DEPTH), &mut |path| {
+            if seen.insert(path.clone()) {
+                files.push((path, Some(root.path.clone())));
+            }
+        });
+    }
+    files
+}
 
-        let metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
-        let modified = metadata
-            .modified()
-            .map(|t| {
-                t.duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis() as u64
-            })
-            .unwrap_or(0);
-
-        let media_type = if ["mp4", "avi"].contains(&ext.as_str()) {
-            Some("video".to_string())
-        } else {
-            Some("image".to_string())
+/// Gallery location of a media path under the same rules as `collect_gallery_files`:
+/// `Some(None)` for the outputs folder, `Some(Some(root))` for an extra gallery root, and
+/// `None` if the gallery doesn't list it
+pub(crate) fn gallery_location(path: &Path, outputs_folder: &Path, roots: &[GalleryRoot]) -> Option<Option<String>> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !GALLERY_EXTENSIONS.contains(&ext.as_str()) {
+        return None;
+    }
+    let within = |base: &Path, depth: u32| {
+        let Some(folders) = path.strip_prefix(base).ok().and_then(|rel| rel.parent()) else {
+            return false;
         };
+        let folders: Vec<_> = folders.components().collect();
+        folders.len() as u32 <= depth
+            && folders
+                .iter()
+                .all(|c| !c.as_os_str().to_string_lossy().starts_with('.'))
+    };
+    if within(outputs_folder, 0) {
+        return Some(None);
+    }
+    roots
+        .iter()
+        .find(|root| within(Path::new(&root.path), root.depth.min(MAX_ROOT_DEPTH)))
+        .map(|root| Some(root.path.clone()))
+}
 
-        // Try to load metadata JSON
-        let json_path = path.with_extension("json");
-        let mut info = GeneratedImageInfo {
-            name: name.clone(),
-            path: path.to_string_lossy().to_string(),
-            size: metadata.len(),
-            modified,
-            width: None,
-            height: None,
-            gen_type: None,
-            media_type,
-            prompt: None,
-            negative_prompt: None,
-            steps: None,
-            cfg_scale: None,
-            device_type: None,
-            group_id: None,
-            group_name: None,
-            model_path: None,
-            vae_model_path: None,
-            llm_model_path: None,
-            sampling_method: None,
-            scheduler: None,
-            seed: None,
-            batch_count: None,
-            threads: None,
-            preview_method: None,
-            preview_interval: None,
-            preview_image: None,
-            verbose: None,
-            command_line: None,
-            generated_at: None,
-            duration: None,
-        };
+/// Gallery info for a media file from its file metadata and JSON sidecar
+pub(crate) fn load_media_info(path: &Path, root: Option<String>) -> Option<GeneratedImageInfo> {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
 
-        if json_path.exists() {
-            if let Ok(json_data) = std::fs::read_to_string(&json_path) {
-                if let Ok(meta) = serde_json::from_str::<serde_json::Value>(&json_data) {
-                    info.prompt = meta["prompt"].as_str().map(|s| s.to_string());
-                    info.negative_prompt = meta["negativePrompt"].as_str().map(|s| s.to_string());
-                    info.steps = meta["steps"].as_u64().map(|v| v as u32);
-                    info.cfg_scale = meta["cfgScale"].as_f64();
-                    info.device_type = meta["deviceType"].as_str().map(|s| s.to_string());
-                    info.group_id = meta["groupId"].as_str().map(|s| s.to_string());
-                    info.group_name = meta["groupName"].as_str().map(|s| s.to_string());
-                    info.sampling_method = meta["samplingMethod"].as_str().map(|s| s.to_string());
-                    info.scheduler = meta["scheduler"].as_str().map(|s| s.to_string());
-                    info.seed = meta["seed"].as_i64();
-                    info.gen_type = meta["type"].as_str().map(|s| s.to_string());
-                    info.generated_at = meta["generatedAt"].as_str().map(|s| s.to_string());
-                    info.duration = meta["duration"].as_u64();
-                    info.width = meta["width"].as_u64().map(|v| v as u32);
-                    info.height = meta["height"].as_u64().map(|v| v as u32);
-                }
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .map(|t| {
+            t.duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64
+        })
+        .unwrap_or(0);
+
+    let media_type = if ["mp4", "webm", "avi"].contains(&ext.as_str()) {
+        Some("video".to_string())
+    } else {
+        Some("image".to_string())
+    };
+
+    // Try to load metadata JSON
+    let json_path = path.with_extension("json");
+    let mut info = GeneratedImageInfo {
+        name: name.clone(),
+        path: path.to_string_lossy().to_string(),
+        size: metadata.len(),
+        modified,
+        read_only: root.is_some(),
+        root,
+        width: None,
+        height: None,
+        gen_type: None,
+        media_type,
+        prompt: None,
+        negative_prompt: None,
+        steps: None,
+        cfg_scale: None,
+        device_type: None,
+        group_id: None,
+        group_name: None,
+        model_path: None,
+        vae_model_path: None,
+        llm_model_path: None,
+        sampling_method: None,
+        scheduler: None,
+        seed: None,
+        batch_count: None,
+        threads: None,
+        preview_method: None,
+        preview_interval: None,
+        preview_image: None,
+        verbose: None,
+        command_line: None,
+        models: None,
+        engine: None,
+        regenerated_from: None,
+        source_image: None,
+        source_video: None,
+        source_images: None,
+        source_videos: None,
+        ref_images: None,
+        photo_maker: None,
+        circular_padding: None,
+        tile_preview: None,
+        embeddings: None,
+        video_frames: None,
+        fps: None,
+        video_duration: None,
+        video_codec: None,
+        poster: None,
+        rating: None,
+        favorite: false,
+        tags: Vec::new(),
+        note: None,
+        generated_at: None,
+        duration: None,
+    };
+
+    if json_path.exists() {
+        if let Ok(json_data) = std::fs::read_to_string(&json_path) {
+            if let Ok(meta) = serde_json::from_str::<serde_json::Value>(&json_data) {
+                info.prompt = meta["prompt"].as_str().map(|s| s.to_string());
+                info.negative_prompt = meta["negativePrompt"].as_str().map(|s| s.to_string());
+                info.steps = meta["steps"].as_u64().map(|v| v as u32);
+                info.cfg_scale = meta["cfgScale"].as_f64();
+                info.device_type = meta["deviceType"].as_str().map(|s| s.to_string());
+                info.group_id = meta["groupId"].as_str().map(|s| s.to_string());
+                info.group_name = meta["groupName"].as_str().map(|s| s.to_string());
+                info.sampling_method = meta["samplingMethod"].as_str().map(|s| s.to_string());
+                info.scheduler = meta["scheduler"].as_str().map(|s| s.to_string());
+                info.seed = meta["seed"].as_i64();
+                info.gen_type = meta["type"].as_str().map(|s| s.to_string());
+                info.generated_at = meta["generatedAt"].as_str().map(|s| s.to_string());
+                info.duration = meta["duration"].as_u64();
+                info.width = meta["width"].as_u64().map(|v| v as u32);
+                info.height = meta["height"].as_u64().map(|v| v as u32);
+                info.model_path = meta["modelPath"].as_str().map(|s| s.to_string());
+                info.vae_model_path = meta["vaeModelPath"].as_str().map(|s| s.to_string());
+                info.llm_model_path = meta["llmModelPath"].as_str().map(|s| s.to_string());
+                info.command_line = meta["commandLine"].as_str().map(|s| s.to_string());
+                info.threads = meta["threads"].as_u64().map(|v| v as u32);
+                info.batch_count = meta["batchCount"].as_u64().map(|v| v as u32);
+                info.models = serde_json::from_value(meta["models"].clone()).ok();
+                info.engine = serde_json::from_value(meta["engine"].clone()).ok();
+                info.regenerated_from = meta["regeneratedFrom"].as_str().map(|s| s.to_string());
+                info.source_image = meta["sourceImage"].as_str().map(|s| s.to_string());
+                info.source_video = meta["sourceVideo"].as_str().map(|s| s.to_string());
+                info.source_images = serde_json::from_value(meta["sourceImages"].clone()).ok();
+                info.source_videos = serde_json::from_value(meta["sourceVideos"].clone()).ok();
+                info.ref_images = serde_json::from_value(meta["refImages"].clone()).ok();
+                info.photo_maker = meta.get("photoMaker").filter(|v| !v.is_null()).cloned();
+                info.circular_padding = meta["circularPadding"].as_str().map(|s| s.to_string());
+                info.tile_preview = meta["tilePreview"].as_str().map(|s| s.to_string());
+                info.embeddings = meta.get("embeddings").filter(|v| !v.is_null()).cloned();
+                info.video_frames = meta["videoFrames"].as_u64().map(|v| v as u32);
+                info.fps = meta["fps"].as_f64();
+                info.rating = meta["rating"].as_u64().map(|v| v.min(5) as u8).filter(|v| *v > 0);
+                info.favorite = meta["favorite"].as_bool().unwrap_or(false);
+                info.tags = serde_json::from_value(meta["tags"].clone()).unwrap_or_default();
+                info.note = meta["note"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string());
             }
         }
+    }
 
-        images.push(info);
+    Some(info)
+}
+
+/// Fill in probed stream info and the poster frame for a video
+pub(crate) async fn apply_video_details(info: &mut GeneratedImageInfo, ffmpeg_path: &Path, outputs_folder: &Path) {
+    let path = PathBuf::from(&info.path);
+    let cache_dir = poster_cache_dir(&path, info.read_only, outputs_folder);
+    if let Some(details) = video_details(ffmpeg_path, &path, &cache_dir, info.size, info.modified).await {
+        // The file is the source of truth; sidecars may predate post-processing
+        info.width = details.probe.width.or(info.width);
+        info.height = details.probe.height.or(info.height);
+        info.fps = details.probe.fps.or(info.fps);
+        info.video_frames = details.probe.frame_count.map(|v| v as u32).or(info.video_frames);
+        info.video_duration = details.probe.duration;
+        info.video_codec = details.probe.codec;
+        info.poster = details.poster;
     }
+}
 
-    // Sort by modified time descending
-    images.sort_by(|a, b| b.modified.cmp(&a.modified));
+/// Call `found` for each gallery media file in `dir`, descending `depth` levels of
+/// subfolders. Hidden folders (`.posters`, `.thumbs`, job temp dirs) are skipped
+fn collect_media_files(dir: &Path, depth: u32, found: &mut dyn FnMut(PathBuf)) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut subdirs = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let hidden = path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            if depth > 0 && !hidden {
+                subdirs.push(path);
+            }
+            continue;
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if GALLERY_EXTENSIONS.contains(&ext.as_str()) {
+            found(path);
+        }
+    }
+    for subdir in subdirs {
+        collect_media_files(&subdir, depth - 1, found);
+    }
+}
 
-    Ok(images)
+/// Whether `path` belongs to an extra gallery root rather than the outputs folder
+pub(crate) fn is_read_only_gallery_path(state: &AppState, path: &Path) -> bool {
+    let Ok(path) = dunce::canonicalize(path) else {
+        return false;
+    };
+    let in_folder = |folder: &Path| dunce::canonicalize(folder).is_ok_and(|folder| path.starts_with(folder));
+    if in_folder(&state::get_outputs_folder(state)) {
+        return false;
+    }
+    state
+        .gallery_roots
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|root| in_folder(Path::new(&root.path)))
 }
 
 /// Export (download) a generated image via save dialog
@@ -186,21 +538,216 @@ pub async fn generated_images_download(
     }
 }
 
-/// Delete a generated image/video and its metadata
+/// Set rating, favorite, tags and/or note on gallery items, stored in their sidecars
+#[tauri::command]
+pub async fn generated_images_annotate(
+    value: GalleryAnnotationUpdate,
+    state: State<'_, AppState>,
+) -> Result<Vec<GeneratedImageInfo>, String> {
+    if value.rating.is_some_and(|rating| rating > 5) {
+        return Err("rating must be between 0 and 5".to_string());
+    }
+    if let Some(read_only) = value
+        .paths
+        .iter()
+        .find(|path| is_read_only_gallery_path(&state, Path::new(path)))
+    {
+        return Err(format!("Items from extra gallery roots are read-only: {}", read_only));
+    }
+    let normalize = |tags: &[String]| -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::new();
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                normalized.push(tag.to_string());
+            }
+        }
+        normalized
+    };
+
+    let mut updated = Vec::new();
+    for path in &value.paths {
+        let media_path = Path::new(path);
+        if !media_path.is_file() {
+            return Err(format!("File not found: {}", path));
+        }
+        let json_path = media_path.with_extension("json");
+        // Files without a sidecar (e.g. copied in by hand) get a minimal one
+        let mut meta = std::fs::read_to_string(&json_path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+            .filter(|meta| meta.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+
+        if let Some(rating) = value.rating {
+            meta["rating"] = if rating == 0 { serde_json::Value::Null } else { rating.into() };
+        }
+        if let Some(favorite) = value.favorite {
+            meta["favorite"] = favorite.into();
+        }
+        if value.tags.is_some() || !value.add_tags.is_empty() || !value.remove_tags.is_empty() {
+            let current: Vec<String> = serde_json::from_value(meta["tags"].clone()).unwrap_or_default();
+            let mut tags = normalize(value.tags.as_deref().unwrap_or(&current));
+            for tag in normalize(&value.add_tags) {
+                if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                    tags.push(tag);
+                }
+            }
+            let remove = normalize(&value.remove_tags);
+            tags.retain(|t| !remove.iter().any(|r| r.eq_ignore_ascii_case(t)));
+            meta["tags"] = serde_json::json!(tags);
+        }
+        if let Some(note) = &value.note {
+            meta["note"] = if note.trim().is_empty() { serde_json::Value::Null } else { note.clone().into() };
+        }
+
+        std::fs::write(&json_path, serde_json::to_string_pretty(&meta).unwrap_or_default())
+            .map_err(|e| format!("Failed to write {}: {}", json_path.display(), e))?;
+        if let Some(info) = load_media_info(media_path, None) {
+            updated.push(info);
+        }
+    }
+    Ok(updated)
+}
+
+/// All tags used in the gallery with their item counts, most used first
+#[tauri::command]
+pub async fn generated_images_list_tags(state: State<'_, AppState>) -> Result<Vec<GalleryTagCount>, String> {
+    let index = refresh_gallery_index(&state, false).await?;
+    // Tags match case-insensitively; the first spelling seen is reported
+    let mut counts: BTreeMap<String, GalleryTagCount> = BTreeMap::new();
+    for entry in index.iter().flat_map(|index| index.entries.values()) {
+        for tag in &entry.info.tags {
+            counts
+                .entry(tag.to_lowercase())
+                .or_insert_with(|| GalleryTagCount {
+                    tag: tag.clone(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+    }
+    let mut tags: Vec<GalleryTagCount> = counts.into_values().collect();
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    Ok(tags)
+}
+
+/// Move a generated image/video and its metadata to the recycle bin
 #[tauri::command]
-pub async fn generated_images_delete(value: String) -> Result<bool, String> {
+pub async fn generated_images_delete(value: String, state: State<'_, AppState>) -> Result<bool, String> {
     let path = Path::new(&value);
-    if path.exists() {
-        std::fs::remove_file(path).map_err(|e| e.to_string())?;
+    if is_read_only_gallery_path(&state, path) {
+        return Err("Items from extra gallery roots are read-only".to_string());
     }
-    // Also delete metadata JSON
-    let json_path = Path::new(&value).with_extension("json");
-    if json_path.exists() {
-        let _ = std::fs::remove_file(&json_path);
+    // The sidecar stays while another media file with the same stem still uses it
+    let shares_sidecar = GALLERY_EXTENSIONS.iter().any(|ext| {
+        let sibling = path.with_extension(ext);
+        sibling != path && sibling.is_file()
+    });
+    let mut files = vec![path.to_path_buf()];
+    if !shares_sidecar {
+        files.push(path.with_extension("json"));
+    }
+    move_to_trash(&state, TrashKind::Gallery, &files, serde_json::Value::Null)?;
+    // Drop any cached thumbnails and video probe/poster
+    let outputs_folder = state::get_outputs_folder(&state);
+    remove_thumbnails(&outputs_folder, path);
+    let cache_dir = poster_cache_dir(path, false, &outputs_folder);
+    if let Some((cache_path, poster_path)) = video_cache_paths(path, &cache_dir) {
+        let _ = std::fs::remove_file(cache_path);
+        let _ = std::fs::remove_file(poster_path);
     }
     Ok(true)
 }
 
+/// Probe results and poster frame for a video, cached in its poster cache folder
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VideoDetailsCache {
+    size: u64,
+    modified: u64,
+    probe: VideoProbe,
+    poster: Option<String>,
+}
+
+/// Folder for cached probes/posters: `.posters` next to the video, or for read-only gallery
+/// roots a per-folder directory under the outputs folder's `.posters/external`
+fn poster_cache_dir(video_path: &Path, read_only: bool, outputs_folder: &Path) -> PathBuf {
+    let parent = video_path.parent().unwrap_or(Path::new(""));
+    if read_only {
+        let key = format!("{:x}", md5::compute(parent.to_string_lossy().as_bytes()));
+        outputs_folder.join(".posters").join("external").join(key)
+    } else {
+        parent.join(".posters")
+    }
+}
+
+/// Cached poster frame of a video, whether it lives next to the video or, for read-only
+/// gallery roots, under the outputs folder
+pub(crate) fn cached_poster(video_path: &Path, outputs_folder: &Path) -> Option<PathBuf> {
+    [false, true].into_iter().find_map(|read_only| {
+        let cache_dir = poster_cache_dir(video_path, read_only, outputs_folder);
+        let (_, poster_path) = video_cache_paths(video_path, &cache_dir)?;
+        Some(poster_path).filter(|path| path.is_file())
+    })
+}
+
+/// `<file name>.json` and `<file name>.jpg` in the cache folder. The full file name is used
+/// so an AVI kept next to its MP4 gets its own entry
+fn video_cache_paths(video_path: &Path, cache_dir: &Path) -> Option<(PathBuf, PathBuf)> {
+    let name = video_path.file_name()?.to_string_lossy().to_string();
+    Some((cache_dir.join(format!("{}.json", name)), cache_dir.join(format!("{}.jpg", name))))
+}
+
+/// Probe a video and extract a poster frame, reusing the cache while size and mtime match
+async fn video_details(
+    ffmpeg_path: &Path,
+    video_path: &Path,
+    cache_dir: &Path,
+    size: u64,
+    modified: u64,
+) -> Option<VideoDetailsCache> {
+    let (cache_path, poster_path) = video_cache_paths(video_path, cache_dir)?;
+    let cached = std::fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<VideoDetailsCache>(&data).ok());
+    if let Some(cached) = cached.filter(|c| c.size == size && c.modified == modified) {
+        return Some(cached);
+    }
+
+    let probe = probe_video(ffmpeg_path, video_path).await?;
+    std::fs::create_dir_all(cache_path.parent()?).ok()?;
+    // `thumbnail` picks a representative frame rather than the (often static) first one
+    let poster_args = vec![
+        "-i".to_string(),
+        video_path.to_string_lossy().to_string(),
+        "-vf".to_string(),
+        "thumbnail".to_string(),
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-q:v".to_string(),
+        "3".to_string(),
+        "-y".to_string(),
+        poster_path.to_string_lossy().to_string(),
+    ];
+    let poster = match run_ffmpeg(ffmpeg_path, &poster_args).await {
+        Ok(outcome) if outcome.success && poster_path.is_file() => {
+            Some(poster_path.to_string_lossy().to_string())
+        }
+        _ => None,
+    };
+
+    let details = VideoDetailsCache {
+        size,
+        modified,
+        probe,
+        poster,
+    };
+    if let Ok(data) = serde_json::to_string_pretty(&details) {
+        let _ = std::fs::write(&cache_path, data);
+    }
+    Some(details)
+}
+
 /// Get preview image as base64
 #[tauri::command]
 pub async fn generated_images_get_preview(value: String) -> Result<String, String> {
@@ -320,3 +867,186 @@ pub async fn generated_images_batch_download(
         "size": zip_size
     }))
 }
+
+/// Re-run a gallery item from its sidecar metadata, optionally overriding request fields
+#[tauri::command]
+pub async fn generated_images_regenerate(
+    path: String,
+    overrides: Option<serde_json::Value>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let json_path = Path::new(&path).with_extension("json");
+    let data = std::fs::read_to_string(&json_path)
+        .map_err(|_| format!("Metadata not found for: {}", path))?;
+    let meta: serde_json::Value = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+
+    // Derived media has no sd.cpp request of its own to replay
+    let not_regenerable = match meta["type"].as_str() {
+        Some("tile-preview") => Some("Tile previews cannot be regenerated; regenerate the source texture instead"),
+        Some("postprocess") | Some("trim") | Some("frame") => {
+            Some("Derived items cannot be regenerated; regenerate the source video instead")
+        }
+        Some("slideshow") | Some("concat") => {
+            Some("Assembled videos cannot be regenerated; assemble a new one from the gallery instead")
+        }
+        _ => None,
+    };
+    if let Some(message) = not_regenerable {
+        return Err(message.to_string());
+    }
+
+    let mut request = if meta["request"].is_object() {
+        meta["request"].clone()
+    } else {
+        let mut legacy = serde_json::Map::new();
+        for key in LEGACY_REQUEST_KEYS {
+            if !meta[*key].is_null() {
+                legacy.insert(key.to_string(), meta[*key].clone());
+            }
+        }
+        serde_json::Value::Object(legacy)
+    };
+    let overrides = overrides.unwrap_or(serde_json::Value::Null);
+    if let (Some(base), Some(upd)) = (request.as_object_mut(), overrides.as_object()) {
+        for (key, val) in upd {
+            base.insert(key.clone(), val.clone());
+        }
+    }
+
+    let (missing, changed) = check_regenerate_resources(&meta, &request, &overrides, &state);
+    if !missing.is_empty() {
+        return Err(format!(
+            "Cannot regenerate, referenced resources are missing:\n{}",
+            missing.join("\n")
+        ));
+    }
+
+    let mut result = if meta["mediaType"].as_str() == Some("video") {
+        generate_video_start(request, app, state).await?
+    } else if meta["type"].as_str() == Some("outpaint") {
+        generate_outpaint(request, app, state).await?
+    } else {
+        generate_start(request, app, state).await?
+    };
+
+    // Link the new item back to its source
+    let new_path = result["imagePath"]
+        .as_str()
+        .or_else(|| result["videoPath"].as_str())
+        .map(|p| p.to_string());
+    if let Some(new_path) = new_path {
+        annotate_sidecar(
+            Path::new(&new_path),
+            serde_json::json!({ "regeneratedFrom": path, "overrides": overrides }),
+        );
+    }
+    if !changed.is_empty() {
+        result["warnings"] = serde_json::json!(changed);
+    }
+
+    Ok(result)
+}
+
+/// Compare a sidecar's recorded resources with what is on disk now.
+/// Returns (missing, changed): missing entries block the re-run, changed ones are reported as warnings.
+fn check_regenerate_resources(
+    meta: &serde_json::Value,
+    request: &serde_json::Value,
+    overrides: &serde_json::Value,
+    state: &State<'_, AppState>,
+) -> (Vec<String>, Vec<String>) {
+    let mut missing = Vec::new();
+    let mut changed = Vec::new();
+
+    if let Some(group_id) = request["groupId"].as_str().filter(|id| !id.trim().is_empty()) {
+        let weights_folder = state.weights_folder.lock().unwrap().clone();
+        let groups_path = state::get_model_groups_path(weights_folder.as_deref());
+        let exists = load_model_groups(&groups_path)
+            .map(|groups| groups.iter().any(|g| g.id == group_id))
+            .unwrap_or(false);
+        if !exists {
+            let label = meta["groupName"].as_str().unwrap_or(group_id);
+            missing.push(format!("- model group: {} ({})", label, group_id));
+        }
+    }
+
+    // 切换模型组时，原记录的模型文件不再适用
+    if overrides.get("groupId").is_none() {
+        let recorded: BTreeMap<String, ModelFileInfo> =
+            serde_json::from_value(meta["models"].clone()).unwrap_or_default();
+        for (slot, model) in recorded {
+            if overrides.get(&slot).is_some() {
+                continue;
+            }
+            if !Path::new(&model.path).is_file() {
+                missing.push(format!("- {}: {}", slot, model.path));
+                continue;
+            }
+            if let Some(expected) = model.sample_hash.as_deref() {
+                let actual = sample_file_hash(Path::new(&model.path)).map(|(_, hash)| hash);
+                if actual.as_deref() != Some(expected) {
+                    changed.push(format!(
+                        "{}: {} (sample hash {} -> {})",
+                        slot,
+                        model.path,
+                        expected,
+                        actual.unwrap_or_default()
+                    ));
+                }
+            }
+        }
+    }
+
+    for key in INPUT_FILE_KEYS {
+        if let Some(input) = request[*key].as_str().filter(|p| !p.is_empty())
+            && !Path::new(input).is_file()
+        {
+            missing.push(format!("- {}: {}", key, input));
+        }
+    }
+
+    // Control video may be a frame directory or a video file
+    if let Some(control) = request["controlVideo"].as_str().filter(|p| !p.is_empty())
+        && !Path::new(control).exists()
+    {
+        missing.push(format!("- controlVideo: {}", control));
+    }
+
+    if let Some(refs) = request["refImages"].as_array() {
+        for ref_image in refs.iter().filter_map(|r| r.as_str()) {
+            if Path::new(ref_image).is_absolute() && !Path::new(ref_image).is_file() {
+                missing.push(format!("- refImages: {}", ref_image));
+            }
+        }
+    }
+    if let Some(id_images) = request["idImages"].as_array() {
+        for id_image in id_images.iter().filter_map(|i| i.as_str()) {
+            if !Path::new(id_image).is_file() {
+                missing.push(format!("- idImages: {}", id_image));
+            }
+        }
+    }
+
+    (missing, changed)
+}
+
+/// Merge extra fields into a media file's sidecar JSON
+pub(crate) fn annotate_sidecar(media_path: &Path, fields: serde_json::Value) {
+    let json_path = media_path.with_extension("json");
+    let Ok(data) = std::fs::read_to_string(&json_path) else {
+        return;
+    };
+    let Ok(mut meta) = serde_json::from_str::<serde_json::Value>(&data) else {
+        return;
+    };
+    if let (Some(base), Some(extra)) = (meta.as_object_mut(), fields.as_object()) {
+        for (key, val) in extra {
+            base.insert(key.clone(), val.clone());
+        }
+    }
+    let _ = std::fs::write(
+        &json_path,
+        serde_json::to_string_pretty(&meta).unwrap_or_default(),
+    );
+}
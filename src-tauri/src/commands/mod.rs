@@ -1,10 +1,23 @@
+pub mod collections;
+pub mod derived_media;
 pub mod dialog;
+pub mod embeddings;
+pub mod ffmpeg;
+pub mod gallery_index;
 pub mod generate;
 pub mod generated_images;
+pub mod model_convert;
 pub mod model_download;
 pub mod model_groups;
+pub mod outpaint;
 pub mod perfect_pixel;
 pub mod sdcpp;
 pub mod system;
+pub mod thumbnails;
+pub mod trash;
+pub mod video_edit;
+pub mod video_export;
 pub mod video_generate;
+pub mod video_postprocess;
+pub mod video_slideshow;
 pub mod weights;
@@ -0,0 +1,312 @@
+use crate::state::{self, AppState, CancelGuard};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::generate::{configure_command, resolve_generate_model_path, resolve_sdcpp_executable};
+use super::model_groups::{load_model_groups, save_model_groups, ModelGroup};
+
+/// Quantization types accepted by `sd -M convert --type`
+const CONVERT_TYPES: &[&str] = &[
+    "f32", "f16", "bf16", "q8_0", "q6_K", "q5_K", "q5_1", "q5_0", "q4_K", "q4_1", "q4_0", "q3_K",
+    "q2_K",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelConvertRequest {
+    /// Source model (absolute, relative to the weights folder, or a file name within the group folder)
+    pub input_path: String,
+    /// Target quantization type, e.g. "q8_0" or "q4_K"
+    pub output_type: String,
+    /// Output file name; defaults to `<stem>.<type>.gguf`
+    pub output_name: Option<String>,
+    /// Write into this group's folder instead of the weights folder root
+    pub group_id: Option<String>,
+    /// Point group slots that reference the source file at the converted file
+    pub update_group: Option<bool>,
+    pub device_type: Option<String>,
+    pub threads: Option<u32>,
+    pub overwrite: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelConvertProgress {
+    pub stage: String,
+    pub current: Option<u64>,
+    pub total: Option<u64>,
+    pub text: String,
+}
+
+/// Convert a model to GGUF with the chosen quantization via `sd -M convert`
+#[tauri::command]
+pub async fn models_convert(
+    value: ModelConvertRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let output_type = value.output_type.trim().to_string();
+    if !CONVERT_TYPES.contains(&output_type.as_str()) {
+        return Err(format!(
+            "Unsupported quantization type: {} (expected one of: {})",
+            output_type,
+            CONVERT_TYPES.join(", ")
+        ));
+    }
+
+    let sdcpp_folder = state
+        .sdcpp_folder
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("SD.cpp folder not set")?;
+    let device_type = value
+        .device_type
+        .clone()
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or_else(|| state.sdcpp_device_type.lock().unwrap().clone());
+    let device_folder = Path::new(&sdcpp_folder).join(&device_type);
+    let exe_path = resolve_sdcpp_executable(&device_folder).ok_or_else(|| {
+        format!(
+            "SD.cpp executable not found in: {} (expected one of: sd/sd-cli/sd_server/sd-server)",
+            device_folder.display()
+        )
+    })?;
+
+    let weights_folder = state
+        .weights_folder
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| state::get_default_models_folder().to_string_lossy().to_string());
+    let groups_path = state::get_model_groups_path(Some(&weights_folder));
+
+    let group: Option<ModelGroup> = match value.group_id.as_deref().filter(|id| !id.trim().is_empty()) {
+        Some(group_id) => Some(
+            load_model_groups(&groups_path)?
+                .into_iter()
+                .find(|g| g.id == group_id)
+                .ok_or("Model group not found")?,
+        ),
+        None => None,
+    };
+    let group_folder = group.as_ref().map(|g| g.folder.as_str());
+
+    let input_path = resolve_generate_model_path(&value.input_path, &weights_folder, group_folder);
+    if input_path.is_empty() || !Path::new(&input_path).is_file() {
+        return Err(format!("Source model not found: {}", value.input_path));
+    }
+
+    let output_dir = match group_folder.filter(|f| !f.trim().is_empty()) {
+        Some(folder) => Path::new(&weights_folder).join(folder),
+        None => PathBuf::from(&weights_folder),
+    };
+    std::fs::create_dir_all(&output_dir).map_err(|e| e.to_string())?;
+
+    let output_name = match value.output_name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) if name.contains('/') || name.contains('\\') => {
+            return Err("Output name must be a file name, not a path".to_string());
+        }
+        Some(name) if name.to_ascii_lowercase().ends_with(".gguf") => name.to_string(),
+        Some(name) => format!("{}.gguf", name),
+        None => {
+            let stem = Path::new(&input_path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "model".to_string());
+            format!("{}.{}.gguf", stem, output_type)
+        }
+    };
+    let output_path = output_dir.join(&output_name);
+    if output_path.exists() && !value.overwrite.unwrap_or(false) {
+        return Err(format!("Output file already exists: {}", output_path.display()));
+    }
+    // Converted next to the target and renamed into place on success, so a failed or
+    // cancelled re-convert leaves an existing output untouched
+    let partial_path = output_path.with_extension("gguf.part");
+
+    let mut args = vec![
+        "-M".to_string(),
+        "convert".to_string(),
+        "-m".to_string(),
+        input_path.clone(),
+        "-o".to_string(),
+        partial_path.to_string_lossy().to_string(),
+        "--type".to_string(),
+        output_type.clone(),
+    ];
+    if let Some(threads) = value.threads {
+        args.push("-t".to_string());
+        args.push(threads.to_string());
+    }
+
+    let (cancel_tx, mut cancel_rx) = tokio::sync::watch::channel(false);
+    let _cancel_guard = CancelGuard::install(&state.convert_cancel, cancel_tx);
+
+    let _ = app.emit(
+        "models:convert-progress",
+        ModelConvertProgress {
+            stage: "converting".to_string(),
+            current: None,
+            total: None,
+            text: format!("{} -> {}", input_path, output_path.display()),
+        },
+    );
+
+    let mut cmd = tokio::process::Command::new(&exe_path);
+    cmd.args(&args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    configure_command(&mut cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start process: {}", e))?;
+
+    let stdout_task = tokio::spawn(forward_convert_output(child.stdout.take(), app.clone()));
+    let stderr_task = tokio::spawn(forward_convert_output(child.stderr.take(), app.clone()));
+
+    let start = std::time::Instant::now();
+    let exit_status = tokio::select! {
+        status = child.wait() => status,
+        _ = cancel_rx.changed() => {
+            let _ = child.kill().await;
+            let _ = child.wait().await;
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return Ok(serde_json::json!({ "success": false, "error": "cancelled" }));
+        }
+    };
+    let exit_status = match exit_status {
+        Ok(status) => status,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return Err(e.to_string());
+        }
+    };
+
+    let stdout_tail = stdout_task.await.unwrap_or_default();
+    let stderr_tail = stderr_task.await.unwrap_or_default();
+
+    if !exit_status.success() || !partial_path.exists() {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        let detail = if stderr_tail.trim().is_empty() { stdout_tail } else { stderr_tail };
+        return Ok(serde_json::json!({
+            "success": false,
+            "error": format!("Process exited with code: {:?}\n{}", exit_status.code(), detail.trim())
+        }));
+    }
+    if let Err(e) = tokio::fs::rename(&partial_path, &output_path).await {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        return Err(format!("Failed to move converted model into place: {}", e));
+    }
+
+    let mut updated_slots: Vec<&str> = Vec::new();
+    if let (Some(group), true) = (group.as_ref(), value.update_group.unwrap_or(false)) {
+        let mut groups = load_model_groups(&groups_path)?;
+        if let Some(target) = groups.iter_mut().find(|g| g.id == group.id) {
+            // 输出文件位于组目录（或权重目录）下，直接记录文件名即可
+            let new_ref = output_name.clone();
+            let folder = target.folder.clone();
+            for (slot_name, slot) in group_model_slots(target) {
+                let matches = slot.as_deref().is_some_and(|current| {
+                    resolve_generate_model_path(current, &weights_folder, Some(&folder)) == input_path
+                });
+                if matches {
+                    *slot = Some(new_ref.clone());
+                    updated_slots.push(slot_name);
+                }
+            }
+            if !updated_slots.is_empty() {
+                target.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+                save_model_groups(&groups_path, &groups)?;
+            }
+        }
+    }
+
+    let output_size = std::fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0);
+    let _ = app.emit(
+        "models:convert-progress",
+        ModelConvertProgress {
+            stage: "done".to_string(),
+            current: None,
+            total: None,
+            text: output_path.to_string_lossy().to_string(),
+        },
+    );
+
+    Ok(serde_json::json!({
+        "success": true,
+        "outputPath": output_path.to_string_lossy(),
+        "size": output_size,
+        "duration": start.elapsed().as_millis() as u64,
+        "updatedSlots": updated_slots,
+    }))
+}
+
+/// Cancel a running model conversion
+#[tauri::command]
+pub async fn models_cancel_convert(state: State<'_, AppState>) -> Result<bool, String> {
+    if let Some(cancel) = state.convert_cancel.lock().unwrap().take() {
+        let _ = cancel.send(true);
+    }
+    Ok(true)
+}
+
+/// Model file slots of a group, paired with their camelCase field names
+fn group_model_slots(group: &mut ModelGroup) -> Vec<(&'static str, &mut Option<String>)> {
+    vec![
+        ("sdModel", &mut group.sd_model),
+        ("diffusionModel", &mut group.diffusion_model),
+        ("highNoiseSdModel", &mut group.high_noise_sd_model),
+        ("vaeModel", &mut group.vae_model),
+        ("llmModel", &mut group.llm_model),
+        ("clipLModel", &mut group.clip_l_model),
+        ("t5xxlModel", &mut group.t5xxl_model),
+        ("clipVisionModel", &mut group.clip_vision_model),
+        ("taesdModel", &mut group.taesd_model),
+        ("llmVisionModel", &mut group.llm_vision_model),
+        ("photoMakerModel", &mut group.photo_maker_model),
+    ]
+}
+
+/// Forward sd.cpp output as progress events; returns the last lines for error reporting
+async fn forward_convert_output<R>(reader: Option<R>, app: AppHandle) -> String
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    let mut tail: Vec<String> = Vec::new();
+    let Some(reader) = reader else {
+        return String::new();
+    };
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let (current, total) = parse_progress_fraction(&line).unzip();
+        let _ = app.emit(
+            "models:convert-progress",
+            ModelConvertProgress {
+                stage: "converting".to_string(),
+                current,
+                total,
+                text: line.clone(),
+            },
+        );
+        tail.push(line);
+        if tail.len() > 10 {
+            tail.remove(0);
+        }
+    }
+    tail.join("\n")
+}
+
+/// Extract `current/total` from sd.cpp progress bars such as `|=====>   | 120/830 - 35.2it/s`
+fn parse_progress_fraction(line: &str) -> Option<(u64, u64)> {
+    line.split_whitespace().find_map(|token| {
+        let (current, total) = token.split_once('/')?;
+        let current = current.parse::<u64>().ok()?;
+        let total = total.parse::<u64>().ok()?;
+        (total > 0 && current <= total).then_some((current, total))
+    })
+}
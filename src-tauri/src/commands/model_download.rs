@@ -10,6 +10,8 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter, State};
 
+use super::trash::{move_to_trash, TrashKind};
+
 const VERIFIED_EXTENSION: &str = "verified";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -733,12 +735,14 @@ pub async fn models_delete_file(
         return Ok(true);
     }
 
+    // The verification marker goes along so a restored file doesn't need re-verifying
     let verified_path = get_verified_path(&full_path);
-    if verified_path.exists() {
-        std::fs::remove_file(&verified_path).map_err(|e| e.to_string())?;
-    }
-
-    std::fs::remove_file(&full_path).map_err(|e| e.to_string())?;
+    move_to_trash(
+        &state,
+        TrashKind::Weights,
+        &[full_path, verified_path],
+        serde_json::json!({ "groupId": group_id, "filePath": file_path }),
+    )?;
 
     Ok(true)
 }
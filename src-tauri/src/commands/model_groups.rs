@@ -19,6 +19,9 @@ pub struct ModelGroup {
     pub clip_l_model: Option<String>,
     pub t5xxl_model: Option<String>,
     pub clip_vision_model: Option<String>,
+    pub taesd_model: Option<String>,
+    pub llm_vision_model: Option<String>,
+    pub photo_maker_model: Option<String>,
     pub hf_files: Option<Vec<serde_json::Value>>,
     pub default_steps: Option<u32>,
     pub default_cfg_scale: Option<f64>,
@@ -40,7 +43,7 @@ fn get_model_groups_path(state: &State<'_, state::AppState>) -> std::path::PathB
     state::get_model_groups_path(weights_folder.as_deref())
 }
 
-fn load_model_groups(path: &Path) -> Result<Vec<ModelGroup>, String> {
+pub(crate) fn load_model_groups(path: &Path) -> Result<Vec<ModelGroup>, String> {
     if !path.exists() {
         return Ok(vec![]);
     }
@@ -48,7 +51,54 @@ fn load_model_groups(path: &Path) -> Result<Vec<ModelGroup>, String> {
     serde_json::from_str(&data).map_err(|e| e.to_string())
 }
 
-fn save_model_groups(path: &Path, groups: &[ModelGroup]) -> Result<(), String> {
+/// Request fields filled from a group's `default*` settings, as (request key, group key)
+const GROUP_DEFAULT_FIELDS: &[(&str, &str)] = &[
+    ("steps", "defaultSteps"),
+    ("cfgScale", "defaultCfgScale"),
+    ("width", "defaultWidth"),
+    ("height", "defaultHeight"),
+    ("samplingMethod", "defaultSamplingMethod"),
+    ("scheduler", "defaultScheduler"),
+    ("seed", "defaultSeed"),
+    ("flowShift", "defaultFlowShift"),
+    ("highNoiseSteps", "defaultHighNoiseSteps"),
+    ("highNoiseCfgScale", "defaultHighNoiseCfgScale"),
+    ("highNoiseSamplingMethod", "defaultHighNoiseSamplingMethod"),
+];
+
+/// Merge the defaults of the request's `groupId` group under the request.
+/// Fields the request sets (non-null, non-empty) win; unknown groups leave the request as is.
+pub(crate) fn apply_group_defaults(params: serde_json::Value, state: &state::AppState) -> serde_json::Value {
+    let Some(group_id) = params["groupId"].as_str().filter(|id| !id.is_empty()) else {
+        return params;
+    };
+    let weights_folder = state.weights_folder.lock().unwrap().clone();
+    let groups_path = state::get_model_groups_path(weights_folder.as_deref());
+    let Some(group) = load_model_groups(&groups_path)
+        .ok()
+        .and_then(|groups| groups.into_iter().find(|g| g.id == group_id))
+    else {
+        return params;
+    };
+    let group = serde_json::to_value(group).unwrap_or_default();
+
+    let mut merged = params;
+    if let Some(obj) = merged.as_object_mut() {
+        for (request_key, group_key) in GROUP_DEFAULT_FIELDS {
+            let unset = match obj.get(*request_key) {
+                None | Some(serde_json::Value::Null) => true,
+                Some(serde_json::Value::String(s)) => s.is_empty(),
+                Some(_) => false,
+            };
+            if unset && !group[*group_key].is_null() {
+                obj.insert(request_key.to_string(), group[*group_key].clone());
+            }
+        }
+    }
+    merged
+}
+
+pub(crate) fn save_model_groups(path: &Path, groups: &[ModelGroup]) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
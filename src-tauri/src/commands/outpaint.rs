@@ -0,0 +1,136 @@
+use crate::imaging::{self, RgbaImage};
+use crate::state::{self, AppState};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use super::generate::generate_start;
+use super::generated_images::annotate_sidecar;
+
+/// Request fields consumed by the outpaint step and not forwarded to sd.cpp
+const OUTPAINT_KEYS: &[&str] = &["sourceImage", "extend", "overlap"];
+
+/// sd.cpp expects image sizes aligned to this many pixels
+const DIMENSION_ALIGN: u32 = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OutpaintExtend {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+/// Extend an image on any side and inpaint the new regions
+#[tauri::command]
+pub async fn generate_outpaint(
+    value: serde_json::Value,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let source_image = value["sourceImage"]
+        .as_str()
+        .filter(|p| !p.is_empty())
+        .ok_or("sourceImage is required")?
+        .to_string();
+    let mut extend: OutpaintExtend =
+        serde_json::from_value(value["extend"].clone()).map_err(|e| e.to_string())?;
+    if extend.left + extend.top + extend.right + extend.bottom == 0 {
+        return Err("At least one side must be extended".to_string());
+    }
+    let overlap = value["overlap"].as_u64().unwrap_or(8) as u32;
+
+    let source = imaging::read_png(Path::new(&source_image))?;
+
+    // Round the canvas up to sd.cpp's alignment by growing the right/bottom edges
+    let width = source.width + extend.left + extend.right;
+    let height = source.height + extend.top + extend.bottom;
+    extend.right += width.next_multiple_of(DIMENSION_ALIGN) - width;
+    extend.bottom += height.next_multiple_of(DIMENSION_ALIGN) - height;
+
+    let (canvas, mask) = build_outpaint_canvas(&source, extend, overlap);
+
+    let outputs_folder = state
+        .outputs_folder
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| state::get_default_outputs_folder().to_string_lossy().to_string());
+    let work_dir = Path::new(&outputs_folder)
+        .join(".outpaint")
+        .join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&work_dir).map_err(|e| e.to_string())?;
+    let canvas_path = work_dir.join("canvas.png");
+    let mask_path = work_dir.join("mask.png");
+    imaging::write_png(&canvas_path, &canvas)?;
+    imaging::write_png(&mask_path, &mask)?;
+
+    let mut request = value.clone();
+    if let Some(obj) = request.as_object_mut() {
+        for key in OUTPAINT_KEYS {
+            obj.remove(*key);
+        }
+        obj.insert("inputImage".to_string(), serde_json::json!(canvas_path.to_string_lossy()));
+        obj.insert("maskImage".to_string(), serde_json::json!(mask_path.to_string_lossy()));
+        obj.insert("width".to_string(), serde_json::json!(canvas.width));
+        obj.insert("height".to_string(), serde_json::json!(canvas.height));
+        obj.entry("strength").or_insert(serde_json::json!(1.0));
+    }
+
+    let result = generate_start(request, app, state).await;
+    let _ = std::fs::remove_dir_all(&work_dir);
+    let mut result = result?;
+
+    if let Some(image_path) = result["imagePath"].as_str() {
+        annotate_sidecar(
+            Path::new(image_path),
+            serde_json::json!({
+                "type": "outpaint",
+                "sourceImage": source_image,
+                "outpaint": {
+                    "left": extend.left,
+                    "top": extend.top,
+                    "right": extend.right,
+                    "bottom": extend.bottom,
+                    "overlap": overlap,
+                },
+                "request": value,
+            }),
+        );
+        result["width"] = serde_json::json!(canvas.width);
+        result["height"] = serde_json::json!(canvas.height);
+    }
+
+    Ok(result)
+}
+
+/// Build the enlarged canvas (new regions filled by replicating the nearest edge pixel)
+/// and the inpainting mask (white = generate, black = keep). The mask reaches `overlap`
+/// pixels into the source along extended edges so the seam gets blended.
+fn build_outpaint_canvas(source: &RgbaImage, extend: OutpaintExtend, overlap: u32) -> (RgbaImage, RgbaImage) {
+    let width = source.width + extend.left + extend.right;
+    let height = source.height + extend.top + extend.bottom;
+    let mut canvas = RgbaImage::new(width, height, [0, 0, 0, 255]);
+    let mut mask = RgbaImage::new(width, height, [0, 0, 0, 255]);
+
+    let inset = |extended: u32| if extended > 0 { overlap } else { 0 };
+    let keep_left = extend.left + inset(extend.left);
+    let keep_top = extend.top + inset(extend.top);
+    let keep_right = (extend.left + source.width).saturating_sub(inset(extend.right));
+    let keep_bottom = (extend.top + source.height).saturating_sub(inset(extend.bottom));
+
+    for y in 0..height {
+        let sy = y.saturating_sub(extend.top).min(source.height - 1);
+        for x in 0..width {
+            let sx = x.saturating_sub(extend.left).min(source.width - 1);
+            canvas.put(x, y, source.get(sx, sy));
+            let keep = x >= keep_left && x < keep_right && y >= keep_top && y < keep_bottom;
+            if !keep {
+                mask.put(x, y, [255, 255, 255, 255]);
+            }
+        }
+    }
+
+    (canvas, mask)
+}
@@ -17,6 +17,7 @@ fn configure_command(cmd: &mut tokio::process::Command) {
 #[cfg(not(target_os = "windows"))]
 fn configure_command(_cmd: &mut tokio::process::Command) {}
 
+use super::trash::{move_to_trash, TrashKind};
 use super::weights::WeightFile;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -204,7 +205,7 @@ pub async fn sdcpp_list_files(
     }))
 }
 
-async fn get_sdcpp_version(device_folder: &Path) -> Option<String> {
+pub(crate) async fn get_sdcpp_version(device_folder: &Path) -> Option<String> {
     let candidates: &[&str] = if cfg!(target_os = "windows") {
         &["sd-cli.exe", "sd.exe", "sd_server.exe", "sd-server.exe"]
     } else {
@@ -283,16 +284,11 @@ pub async fn sdcpp_download_file(value: String, app: AppHandle) -> Result<bool,
     }
 }
 
-/// Delete an engine file
+/// Move an engine file to the recycle bin
 #[tauri::command]
-pub async fn sdcpp_delete_file(value: String) -> Result<bool, String> {
-    let path = Path::new(&value);
-    if path.exists() {
-        std::fs::remove_file(path).map_err(|e| e.to_string())?;
-        Ok(true)
-    } else {
-        Ok(false)
-    }
+pub async fn sdcpp_delete_file(value: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let trashed = move_to_trash(&state, TrashKind::Engine, &[PathBuf::from(&value)], serde_json::Value::Null)?;
+    Ok(trashed.is_some())
 }
 
 /// Fetch releases from GitHub
@@ -655,7 +651,7 @@ pub async fn sdcpp_download_engine(
     Ok(serde_json::json!({ "success": true, "extractedFiles": extracted_files.len() }))
 }
 
-fn extract_zip(zip_path: &Path, dest: &Path) -> Result<Vec<PathBuf>, String> {
+pub(crate) fn extract_zip(zip_path: &Path, dest: &Path) -> Result<Vec<PathBuf>, String> {
     let file = std::fs::File::open(zip_path).map_err(|e| e.to_string())?;
     let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
     let mut extracted_files: Vec<PathBuf> = Vec::new();
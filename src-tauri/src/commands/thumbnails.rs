@@ -0,0 +1,93 @@
+use crate::imaging;
+use std::path::{Path, PathBuf};
+
+use super::generated_images::cached_poster;
+
+/// Standard thumbnail sizes (longer side, px); requests are rounded up to one of these
+pub const THUMBNAIL_SIZES: &[u32] = &[128, 256, 512];
+
+const JPEG_QUALITY: u8 = 82;
+
+/// Cache folder for thumbnails of every gallery root
+fn thumbs_folder(outputs_folder: &Path) -> PathBuf {
+    outputs_folder.join(".thumbs")
+}
+
+/// Folder holding all thumbnails of one media file, named after the hash of its path
+fn thumbnail_dir(outputs_folder: &Path, media_path: &Path) -> PathBuf {
+    let key = format!("{:x}", md5::compute(media_path.to_string_lossy().as_bytes()));
+    thumbs_folder(outputs_folder).join(key)
+}
+
+/// Smallest standard size that covers the requested one
+fn standard_size(requested: u32) -> u32 {
+    THUMBNAIL_SIZES
+        .iter()
+        .copied()
+        .find(|size| *size >= requested)
+        .unwrap_or(THUMBNAIL_SIZES[THUMBNAIL_SIZES.len() - 1])
+}
+
+/// Path of the cached thumbnail for a gallery item, creating it if needed.
+///
+/// Thumbnails are keyed by media path and mtime (`<md5(path)>/<mtime>_<size>.<ext>`), so an
+/// overwritten file gets a fresh thumbnail; stale ones for the same file are removed then.
+/// Videos are thumbnailed from their cached poster frame. `format` is "jpeg" (default) or
+/// "webp" (lossless, keeps transparency)
+pub(crate) fn get_or_create_thumbnail(
+    outputs_folder: &Path,
+    media_path: &Path,
+    size: u32,
+    format: &str,
+) -> Result<PathBuf, String> {
+    let size = standard_size(size);
+    let ext = if format == "webp" { "webp" } else { "jpg" };
+    let modified = std::fs::metadata(media_path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("{}: {}", media_path.display(), e))?
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    let folder = thumbnail_dir(outputs_folder, media_path);
+    let thumb_path = folder.join(format!("{}_{}.{}", modified, size, ext));
+    if thumb_path.is_file() {
+        return Ok(thumb_path);
+    }
+
+    let is_video = media_path
+        .extension()
+        .is_some_and(|e| ["mp4", "webm", "avi"].contains(&e.to_string_lossy().to_lowercase().as_str()));
+    let source = if is_video {
+        cached_poster(media_path, outputs_folder)
+            .ok_or_else(|| format!("No poster frame for video: {}", media_path.display()))?
+    } else {
+        media_path.to_path_buf()
+    };
+
+    let thumbnail = imaging::read_image(&source)?.resize_to_fit(size);
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    // Write under a temp name so concurrent requests never serve a half-written file
+    let tmp_path = thumb_path.with_extension(format!("{}.{}.tmp", ext, uuid::Uuid::new_v4()));
+    match ext {
+        "webp" => imaging::write_webp(&tmp_path, &thumbnail)?,
+        _ => imaging::write_jpeg(&tmp_path, &thumbnail, JPEG_QUALITY)?,
+    }
+    std::fs::rename(&tmp_path, &thumb_path).map_err(|e| e.to_string())?;
+
+    // Drop thumbnails of older versions of this file
+    let current = format!("{}_", modified);
+    if let Ok(entries) = std::fs::read_dir(&folder) {
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with(&current) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+    Ok(thumb_path)
+}
+
+/// Remove every cached thumbnail of a media file
+pub(crate) fn remove_thumbnails(outputs_folder: &Path, media_path: &Path) {
+    let _ = std::fs::remove_dir_all(thumbnail_dir(outputs_folder, media_path));
+}
@@ -0,0 +1,242 @@
+use crate::state::{self, AppState, TrashPolicy};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Recycle bin folder inside each root; skipped by the gallery and weights listings
+pub(crate) const TRASH_DIR_NAME: &str = ".trash";
+
+/// What a trashed entry was deleted from; decides which root's bin holds it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Gallery,
+    Weights,
+    Engine,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedFile {
+    pub original_path: String,
+    /// File name inside the entry's `files` folder
+    pub stored_name: String,
+}
+
+/// One deletion: the main file plus companions (sidecar, verification marker) removed with it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    pub id: String,
+    pub kind: TrashKind,
+    pub original_path: String,
+    pub files: Vec<TrashedFile>,
+    /// Total size of the files in bytes
+    pub size: u64,
+    /// Deletion time in milliseconds since the epoch
+    pub deleted_at: u64,
+    /// Extra details from the deleting command, e.g. the model group
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub metadata: serde_json::Value,
+}
+
+/// Root folder whose bin receives deletions of `kind`
+fn trash_root(state: &AppState, kind: TrashKind) -> PathBuf {
+    match kind {
+        TrashKind::Gallery => state::get_outputs_folder(state),
+        TrashKind::Weights => state::get_active_models_folder(state.weights_folder.lock().unwrap().as_deref()),
+        TrashKind::Engine => state
+            .sdcpp_folder
+            .lock()
+            .unwrap()
+            .clone()
+            .map(PathBuf::from)
+            .unwrap_or_else(state::get_default_sdcpp_folder),
+    }
+}
+
+/// Every bin folder, one per distinct root
+fn trash_folders(state: &AppState) -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = Vec::new();
+    for kind in [TrashKind::Gallery, TrashKind::Weights, TrashKind::Engine] {
+        let folder = trash_root(state, kind).join(TRASH_DIR_NAME);
+        if !folders.contains(&folder) {
+            folders.push(folder);
+        }
+    }
+    folders
+}
+
+/// All entries with their folders, newest first
+fn load_entries(state: &AppState) -> Vec<(PathBuf, TrashEntry)> {
+    let mut entries = Vec::new();
+    for folder in trash_folders(state) {
+        let Ok(dir) = std::fs::read_dir(&folder) else {
+            continue;
+        };
+        for item in dir.flatten() {
+            let entry_dir = item.path();
+            let entry = std::fs::read(entry_dir.join("entry.json"))
+                .ok()
+                .and_then(|data| serde_json::from_slice::<TrashEntry>(&data).ok());
+            if let Some(entry) = entry {
+                entries.push((entry_dir, entry));
+            }
+        }
+    }
+    entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.deleted_at));
+    entries
+}
+
+/// Rename, falling back to copy + delete when source and destination are on different volumes
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to).map_err(|e| format!("{}: {}", from.display(), e))?;
+    std::fs::remove_file(from).map_err(|e| format!("{}: {}", from.display(), e))
+}
+
+/// Move `files` (the first being the main one) into the recycle bin as a single entry, then
+/// apply the purge policy. Missing files are skipped; returns `None` if none existed
+pub(crate) fn move_to_trash(
+    state: &AppState,
+    kind: TrashKind,
+    files: &[PathBuf],
+    metadata: serde_json::Value,
+) -> Result<Option<TrashEntry>, String> {
+    let existing: Vec<&PathBuf> = files.iter().filter(|path| path.is_file()).collect();
+    let Some(main) = existing.first() else {
+        return Ok(None);
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let entry_dir = trash_root(state, kind).join(TRASH_DIR_NAME).join(&id);
+    let files_dir = entry_dir.join("files");
+    std::fs::create_dir_all(&files_dir).map_err(|e| e.to_string())?;
+
+    let mut entry = TrashEntry {
+        id,
+        kind,
+        original_path: main.to_string_lossy().to_string(),
+        files: Vec::new(),
+        size: 0,
+        deleted_at: chrono::Utc::now().timestamp_millis() as u64,
+        metadata,
+    };
+    for (index, path) in existing.iter().enumerate() {
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let stored_name = format!(
+            "{}_{}",
+            index,
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        move_file(path, &files_dir.join(&stored_name))?;
+        entry.size += size;
+        entry.files.push(TrashedFile {
+            original_path: path.to_string_lossy().to_string(),
+            stored_name,
+        });
+        // Keep the record current so a failure midway still leaves the moved files restorable
+        let data = serde_json::to_string_pretty(&entry).map_err(|e| e.to_string())?;
+        std::fs::write(entry_dir.join("entry.json"), data).map_err(|e| e.to_string())?;
+    }
+
+    apply_trash_policy(state, Some(&entry.id));
+    Ok(Some(entry))
+}
+
+/// Purge entries older than the policy's age limit, then the oldest ones while the bin is over
+/// its size limit. `keep` is never purged, so a single deletion larger than the size limit
+/// can still be undone. Returns the bytes freed
+fn apply_trash_policy(state: &AppState, keep: Option<&str>) -> u64 {
+    let policy = state.trash_policy.lock().unwrap().clone();
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    let max_age_ms = policy.max_age_days as u64 * 24 * 60 * 60 * 1000;
+    let max_size = policy.max_size_mb.saturating_mul(1024 * 1024);
+
+    let mut freed = 0;
+    let mut total = 0;
+    for (entry_dir, entry) in load_entries(state) {
+        let is_kept = keep == Some(entry.id.as_str());
+        let expired = max_age_ms > 0 && now.saturating_sub(entry.deleted_at) > max_age_ms;
+        let over_size = max_size > 0 && total + entry.size > max_size;
+        if !is_kept && (expired || over_size) && std::fs::remove_dir_all(&entry_dir).is_ok() {
+            freed += entry.size;
+        } else {
+            total += entry.size;
+        }
+    }
+    freed
+}
+
+/// List recycle bin entries, newest first
+#[tauri::command]
+pub async fn trash_list(state: State<'_, AppState>) -> Result<Vec<TrashEntry>, String> {
+    apply_trash_policy(&state, None);
+    Ok(load_entries(&state).into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Move an entry's files back to where they were deleted from. Companion files that are
+/// present again (a sidecar shared with another media file of the same stem) are kept as
+/// they are; only the main file must not exist
+#[tauri::command]
+pub async fn trash_restore(value: String, state: State<'_, AppState>) -> Result<TrashEntry, String> {
+    let (entry_dir, entry) = load_entries(&state)
+        .into_iter()
+        .find(|(_, entry)| entry.id == value)
+        .ok_or_else(|| format!("Trash entry not found: {}", value))?;
+
+    if Path::new(&entry.original_path).exists() {
+        return Err(format!("A file already exists at {}", entry.original_path));
+    }
+    for file in &entry.files {
+        let original = Path::new(&file.original_path);
+        if file.original_path != entry.original_path && original.exists() {
+            continue;
+        }
+        if let Some(parent) = original.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        move_file(&entry_dir.join("files").join(&file.stored_name), original)?;
+    }
+    let _ = std::fs::remove_dir_all(&entry_dir);
+    Ok(entry)
+}
+
+/// Permanently delete the given entries; returns the bytes freed
+#[tauri::command]
+pub async fn trash_purge(value: Vec<String>, state: State<'_, AppState>) -> Result<u64, String> {
+    let mut freed = 0;
+    for (entry_dir, entry) in load_entries(&state) {
+        if value.contains(&entry.id) {
+            std::fs::remove_dir_all(&entry_dir).map_err(|e| e.to_string())?;
+            freed += entry.size;
+        }
+    }
+    Ok(freed)
+}
+
+/// Permanently delete everything in the recycle bin; returns the bytes freed
+#[tauri::command]
+pub async fn trash_empty(state: State<'_, AppState>) -> Result<u64, String> {
+    let mut freed = 0;
+    for (entry_dir, entry) in load_entries(&state) {
+        std::fs::remove_dir_all(&entry_dir).map_err(|e| e.to_string())?;
+        freed += entry.size;
+    }
+    Ok(freed)
+}
+
+#[tauri::command]
+pub async fn trash_get_policy(state: State<'_, AppState>) -> Result<TrashPolicy, String> {
+    Ok(state.trash_policy.lock().unwrap().clone())
+}
+
+/// Update the purge policy and apply it right away
+#[tauri::command]
+pub async fn trash_set_policy(value: TrashPolicy, state: State<'_, AppState>) -> Result<TrashPolicy, String> {
+    *state.trash_policy.lock().unwrap() = value.clone();
+    apply_trash_policy(&state, None);
+    Ok(value)
+}
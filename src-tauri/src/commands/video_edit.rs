@@ -0,0 +1,300 @@
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::ffmpeg::{probe_video, resolve_ffmpeg_path, run_ffmpeg, VideoEncodeProfile, VideoProbe};
+use super::generate::JobTempDir;
+use super::derived_media::{
+    encode_failure, extend_metadata, inherited_metadata, new_output_path, read_sidecar, video_result,
+    write_sidecar,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoTrimRequest {
+    pub path: String,
+    /// Start time in seconds (default 0)
+    pub start: Option<f64>,
+    /// End time in seconds (default: end of the video)
+    pub end: Option<f64>,
+    pub encoding: Option<VideoEncodeProfile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoConcatRequest {
+    /// Gallery videos in playback order; all must share resolution and frame rate
+    pub paths: Vec<String>,
+    /// Re-encode with this profile; without it, matching streams are copied losslessly
+    pub encoding: Option<VideoEncodeProfile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoExtractFrameRequest {
+    pub path: String,
+    /// Zero-based frame index
+    pub frame: Option<u64>,
+    /// Time in seconds; used when `frame` is not set
+    pub time: Option<f64>,
+    /// Extract the last frame, e.g. to seed the next image-to-video run
+    #[serde(default)]
+    pub last: bool,
+}
+
+/// Cut a gallery video to a time range, saving the clip as a new gallery item
+#[tauri::command]
+pub async fn video_trim(
+    value: VideoTrimRequest,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let source = Path::new(&value.path);
+    if !source.is_file() {
+        return Err(format!("File not found: {}", value.path));
+    }
+    let start = value.start.unwrap_or(0.0);
+    if start < 0.0 || value.end.is_some_and(|end| end <= start) {
+        return Err("Invalid range: start must be >= 0 and end must be after start".to_string());
+    }
+
+    let ffmpeg_path = resolve_ffmpeg_path(&state)?;
+    let encode_profile = value.encoding.clone().unwrap_or_default();
+    let (encode_args, encoded_ext) = encode_profile.encode_args()?;
+    let (output_path, _) = new_output_path(&state, encoded_ext);
+    let started = std::time::Instant::now();
+
+    let mut args = vec!["-ss".to_string(), start.to_string(), "-i".to_string(), value.path.clone()];
+    if let Some(end) = value.end {
+        args.extend(["-t".to_string(), (end - start).to_string()]);
+    }
+    args.extend(encode_args);
+    args.extend(["-an".to_string(), "-y".to_string(), output_path.to_string_lossy().to_string()]);
+    let outcome = run_ffmpeg(&ffmpeg_path, &args).await?;
+    if !outcome.success || !output_path.exists() {
+        return Ok(encode_failure(&output_path, &outcome).await);
+    }
+
+    let probe = probe_video(&ffmpeg_path, &output_path).await.unwrap_or_default();
+    let mut metadata = inherited_metadata(&read_sidecar(source));
+    extend_metadata(
+        &mut metadata,
+        serde_json::json!({
+            "type": "trim",
+            "mediaType": "video",
+            "sourceVideo": value.path,
+            "width": probe.width,
+            "height": probe.height,
+            "fps": probe.fps,
+            "trim": { "start": start, "end": value.end },
+            "encoding": &encode_profile,
+            "encodeResult": &outcome,
+            "duration": started.elapsed().as_millis() as u64,
+            "generatedAt": chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+    write_sidecar(&output_path, &metadata).await;
+
+    Ok(video_result(&output_path, started))
+}
+
+/// Join gallery videos end to end, saving the result as a new gallery item
+#[tauri::command]
+pub async fn video_concat(
+    value: VideoConcatRequest,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    if value.paths.len() < 2 {
+        return Err("Select at least two videos to concatenate".to_string());
+    }
+    if let Some(missing) = value.paths.iter().find(|p| !Path::new(p).is_file()) {
+        return Err(format!("File not found: {}", missing));
+    }
+
+    let ffmpeg_path = resolve_ffmpeg_path(&state)?;
+    let mut probes: Vec<VideoProbe> = Vec::new();
+    for path in &value.paths {
+        let probe = probe_video(&ffmpeg_path, Path::new(path))
+            .await
+            .ok_or_else(|| format!("Could not read video stream info: {}", path))?;
+        probes.push(probe);
+    }
+    let first = &probes[0];
+    for (path, probe) in value.paths.iter().zip(&probes).skip(1) {
+        let same_fps = match (first.fps, probe.fps) {
+            (Some(a), Some(b)) => (a - b).abs() < 0.01,
+            _ => true,
+        };
+        if probe.width != first.width || probe.height != first.height || !same_fps {
+            return Err(format!(
+                "Video parameters do not match: {} is {}x{} @ {} fps, expected {}x{} @ {} fps",
+                path,
+                probe.width.unwrap_or(0),
+                probe.height.unwrap_or(0),
+                probe.fps.unwrap_or(0.0),
+                first.width.unwrap_or(0),
+                first.height.unwrap_or(0),
+                first.fps.unwrap_or(0.0),
+            ));
+        }
+    }
+
+    // Stream copy only works when every clip has the same codec and container
+    let source_ext = extension_of(&value.paths[0]);
+    let stream_copy = value.encoding.is_none()
+        && probes.iter().all(|p| p.codec == first.codec)
+        && value.paths.iter().all(|p| extension_of(p) == source_ext)
+        && matches!(source_ext.as_str(), "mp4" | "webm");
+    let encode_profile = value.encoding.clone().unwrap_or_default();
+    let (codec_args, output_ext) = if stream_copy {
+        (vec!["-c".to_string(), "copy".to_string()], source_ext.clone())
+    } else {
+        let (args, ext) = encode_profile.encode_args()?;
+        (args, ext.to_string())
+    };
+    let (output_path, timestamp) = new_output_path(&state, &output_ext);
+    let started = std::time::Instant::now();
+
+    let outputs_folder = output_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let work_dir = JobTempDir::create(outputs_folder.join(".concat").join(timestamp.to_string()))?;
+    let list_path = work_dir.path().join("videos.txt");
+    let mut list = String::from("ffconcat version 1.0\n");
+    for path in &value.paths {
+        let absolute = dunce::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        list.push_str(&format!("file '{}'\n", absolute.to_string_lossy().replace('\'', "'\\''")));
+    }
+    std::fs::write(&list_path, list).map_err(|e| e.to_string())?;
+
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        list_path.to_string_lossy().to_string(),
+    ];
+    args.extend(codec_args);
+    args.extend(["-an".to_string(), "-y".to_string(), output_path.to_string_lossy().to_string()]);
+    let outcome = run_ffmpeg(&ffmpeg_path, &args).await?;
+    drop(work_dir);
+    if !outcome.success || !output_path.exists() {
+        return Ok(encode_failure(&output_path, &outcome).await);
+    }
+
+    let mut metadata = inherited_metadata(&read_sidecar(Path::new(&value.paths[0])));
+    extend_metadata(
+        &mut metadata,
+        serde_json::json!({
+            "type": "concat",
+            "mediaType": "video",
+            "sourceVideos": &value.paths,
+            "width": first.width,
+            "height": first.height,
+            "fps": first.fps,
+            "concat": { "streamCopy": stream_copy },
+            "encoding": if stream_copy { None } else { Some(&encode_profile) },
+            "encodeResult": &outcome,
+            "duration": started.elapsed().as_millis() as u64,
+            "generatedAt": chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+    write_sidecar(&output_path, &metadata).await;
+
+    Ok(video_result(&output_path, started))
+}
+
+/// Save one frame of a gallery video (by index, by time, or the last frame) as a PNG gallery item
+#[tauri::command]
+pub async fn video_extract_frame(
+    value: VideoExtractFrameRequest,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let source = Path::new(&value.path);
+    if !source.is_file() {
+        return Err(format!("File not found: {}", value.path));
+    }
+    if value.time.is_some_and(|t| t < 0.0) {
+        return Err("time must be >= 0".to_string());
+    }
+
+    let ffmpeg_path = resolve_ffmpeg_path(&state)?;
+    let (output_path, _) = new_output_path(&state, "png");
+    let output_arg = output_path.to_string_lossy().to_string();
+    let args: Vec<String> = if value.last {
+        // Decode only the tail and keep overwriting the image; the final write is the last frame
+        vec![
+            "-sseof".to_string(),
+            "-1".to_string(),
+            "-i".to_string(),
+            value.path.clone(),
+            "-update".to_string(),
+            "1".to_string(),
+            "-y".to_string(),
+            output_arg,
+        ]
+    } else if let Some(frame) = value.frame {
+        vec![
+            "-i".to_string(),
+            value.path.clone(),
+            "-vf".to_string(),
+            format!("select=eq(n\\,{})", frame),
+            "-frames:v".to_string(),
+            "1".to_string(),
+            "-fps_mode".to_string(),
+            "passthrough".to_string(),
+            "-y".to_string(),
+            output_arg,
+        ]
+    } else {
+        vec![
+            "-ss".to_string(),
+            value.time.unwrap_or(0.0).to_string(),
+            "-i".to_string(),
+            value.path.clone(),
+            "-frames:v".to_string(),
+            "1".to_string(),
+            "-y".to_string(),
+            output_arg,
+        ]
+    };
+    run_ffmpeg(&ffmpeg_path, &args).await?.into_result()?;
+    if !output_path.exists() {
+        return Err("No frame at the requested position".to_string());
+    }
+
+    let mut metadata = inherited_metadata(&read_sidecar(source));
+    let (width, height) = probe_video(&ffmpeg_path, &output_path)
+        .await
+        .map(|probe| (probe.width, probe.height))
+        .unwrap_or_default();
+    extend_metadata(
+        &mut metadata,
+        serde_json::json!({
+            "type": "frame",
+            "mediaType": "image",
+            "sourceVideo": value.path,
+            "width": width,
+            "height": height,
+            "frame": {
+                "index": value.frame,
+                "time": if value.last || value.frame.is_some() { None } else { Some(value.time.unwrap_or(0.0)) },
+                "last": value.last,
+            },
+            "generatedAt": chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+    write_sidecar(&output_path, &metadata).await;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "image": format!("media://{}", urlencoding::encode(&output_path.to_string_lossy())),
+        "imagePath": output_path.to_string_lossy(),
+    }))
+}
+
+fn extension_of(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
@@ -0,0 +1,167 @@
+use crate::state::{self, AppState};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::ffmpeg::{resolve_ffmpeg_path, run_ffmpeg};
+use super::generate::JobTempDir;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoExportRequest {
+    /// Source video path
+    pub path: String,
+    /// "gif", "webp" or "png-zip"
+    pub format: String,
+    /// Output width in pixels; height keeps the aspect ratio
+    pub width: Option<u32>,
+    /// Output frame rate; defaults to the source frame rate
+    pub fps: Option<f64>,
+    /// Loop forever (default) or play once; ignored for PNG frames
+    #[serde(rename = "loop")]
+    pub loop_playback: Option<bool>,
+    /// WebP quality 0-100
+    pub quality: Option<u32>,
+}
+
+/// Export a generated video as an animated GIF/WebP or a zip of numbered PNG frames
+#[tauri::command]
+pub async fn video_export(
+    value: VideoExportRequest,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let source = Path::new(&value.path);
+    if !source.is_file() {
+        return Err(format!("File not found: {}", value.path));
+    }
+    let ffmpeg_path = resolve_ffmpeg_path(&state)?;
+    if value.width == Some(0) || value.fps.is_some_and(|fps| fps <= 0.0) {
+        return Err("width and fps must be greater than 0".to_string());
+    }
+
+    // Exports live in a subfolder so they don't pick up the source's sidecar metadata
+    let export_dir = state::get_outputs_folder(&state).join("exports");
+    std::fs::create_dir_all(&export_dir).map_err(|e| e.to_string())?;
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "video".to_string());
+    // Timestamped so exporting again with other settings never overwrites an earlier export
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let name = format!("{}_{}", stem, timestamp);
+
+    let mut filters: Vec<String> = Vec::new();
+    if let Some(fps) = value.fps {
+        filters.push(format!("fps={}", fps));
+    }
+    if let Some(width) = value.width {
+        filters.push(format!("scale={}:-2:flags=lanczos", width));
+    }
+    let loop_playback = value.loop_playback.unwrap_or(true);
+
+    let output_path = match value.format.as_str() {
+        "gif" => {
+            let output_path = export_dir.join(format!("{}.gif", name));
+            // Two-pass palette in one filter graph for far better colors than the default palette
+            let base = if filters.is_empty() { "null".to_string() } else { filters.join(",") };
+            let graph = format!(
+                "{},split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer:bayer_scale=5",
+                base
+            );
+            let args = vec![
+                "-i".to_string(),
+                value.path.clone(),
+                "-filter_complex".to_string(),
+                graph,
+                "-loop".to_string(),
+                if loop_playback { "0" } else { "-1" }.to_string(),
+                "-y".to_string(),
+                output_path.to_string_lossy().to_string(),
+            ];
+            run_ffmpeg(&ffmpeg_path, &args).await?.into_result()?;
+            output_path
+        }
+        "webp" => {
+            let output_path = export_dir.join(format!("{}.webp", name));
+            let quality = value.quality.unwrap_or(80).min(100);
+            let mut args = vec!["-i".to_string(), value.path.clone()];
+            if !filters.is_empty() {
+                args.extend(["-vf".to_string(), filters.join(",")]);
+            }
+            args.extend([
+                "-c:v".to_string(),
+                "libwebp".to_string(),
+                "-quality".to_string(),
+                quality.to_string(),
+                "-loop".to_string(),
+                if loop_playback { "0" } else { "1" }.to_string(),
+                "-an".to_string(),
+                "-y".to_string(),
+                output_path.to_string_lossy().to_string(),
+            ]);
+            run_ffmpeg(&ffmpeg_path, &args).await?.into_result()?;
+            output_path
+        }
+        "png-zip" => {
+            let output_path = export_dir.join(format!("{}_frames.zip", name));
+            let frames_dir = JobTempDir::create(export_dir.join(format!(".{}_frames", name)))?;
+            let mut args = vec!["-i".to_string(), value.path.clone()];
+            if !filters.is_empty() {
+                args.extend(["-vf".to_string(), filters.join(",")]);
+            }
+            args.extend([
+                "-y".to_string(),
+                frames_dir.path().join("frame_%05d.png").to_string_lossy().to_string(),
+            ]);
+            run_ffmpeg(&ffmpeg_path, &args).await?.into_result()?;
+
+            let frames_path = frames_dir.path().to_path_buf();
+            let zip_path = output_path.clone();
+            tokio::task::spawn_blocking(move || zip_frames(&frames_path, &zip_path))
+                .await
+                .map_err(|e| e.to_string())??;
+            output_path
+        }
+        other => {
+            return Err(format!("Unsupported export format: {} (expected gif, webp or png-zip)", other));
+        }
+    };
+
+    let size = std::fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0);
+    Ok(serde_json::json!({
+        "success": true,
+        "outputPath": output_path.to_string_lossy(),
+        "size": size,
+    }))
+}
+
+/// Zip the PNG frames in `dir` in name order; PNG is already compressed, so entries are stored
+fn zip_frames(dir: &Path, zip_path: &Path) -> Result<(), String> {
+    let mut frames: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "png"))
+        .collect();
+    if frames.is_empty() {
+        return Err("FFmpeg produced no frames".to_string());
+    }
+    frames.sort();
+
+    let file = std::fs::File::create(zip_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored);
+    for frame in &frames {
+        let name = frame.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let data = std::fs::read(frame).map_err(|e| e.to_string())?;
+        zip.start_file(&name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -1,33 +1,16 @@
 use crate::state::{self, AppState};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tauri::{AppHandle, Emitter, State};
 use tokio::io::{AsyncBufReadExt, BufReader};
 
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-#[cfg(target_os = "windows")]
-fn configure_command(cmd: &mut tokio::process::Command) {
-    use std::os::windows::process::CommandExt;
-    cmd.as_std_mut().creation_flags(CREATE_NO_WINDOW);
-}
-
-#[cfg(not(target_os = "windows"))]
-fn configure_command(_cmd: &mut tokio::process::Command) {}
-
-fn resolve_sdcpp_executable(device_folder: &Path) -> Option<std::path::PathBuf> {
-    let candidates: &[&str] = if cfg!(target_os = "windows") {
-        &["sd-cli.exe", "sd.exe", "sd_server.exe", "sd-server.exe"]
-    } else {
-        &["sd-cli", "sd", "sd_server", "sd-server"]
-    };
-
-    candidates
-        .iter()
-        .map(|name| device_folder.join(name))
-        .find(|path| path.exists())
-}
+use super::ffmpeg::{resolve_ffmpeg_path, run_ffmpeg, FfmpegOutcome, VideoEncodeProfile};
+use super::generate::{
+    collect_model_files, configure_command, describe_engine, embeddings_dir_arg, lookup_group_name,
+    primary_model_path, prompt_embeddings, quote_args, resolve_generate_model_path,
+    resolve_sdcpp_executable, JobTempDir,
+};
+use super::model_groups::apply_group_defaults;
 
 /// Start video generation
 #[tauri::command]
@@ -36,6 +19,10 @@ pub async fn generate_video_start(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<serde_json::Value, String> {
+    // Fill omitted parameters from the model group's defaults
+    let request = value.clone();
+    let value = apply_group_defaults(value, &state);
+
     let device_type = value["deviceType"].as_str().unwrap_or("cpu");
     let prompt = value["prompt"].as_str().unwrap_or("");
     let mode = value["mode"].as_str().unwrap_or("text2video");
@@ -77,10 +64,45 @@ pub async fn generate_video_start(
 
     // SD.cpp outputs video as an avi file initially
     let output_avi_path = Path::new(&outputs_folder).join(format!("generated_{}.avi", timestamp));
-    let output_mp4_path = Path::new(&outputs_folder).join(format!("generated_{}.mp4", timestamp));
+
+    // Encoding profile for the transcode step; validated before spending time on generation
+    let encode_profile: VideoEncodeProfile = match value.get("encoding") {
+        Some(encoding) if !encoding.is_null() => {
+            serde_json::from_value(encoding.clone()).map_err(|e| format!("Invalid encoding profile: {}", e))?
+        }
+        _ => VideoEncodeProfile::default(),
+    };
+    let (encode_args, encoded_ext) = encode_profile.encode_args()?;
+    let encoded_path = Path::new(&outputs_folder).join(format!("generated_{}.{}", timestamp, encoded_ext));
+
+    // Control video frames (a frame directory, or a video file extracted with ffmpeg)
+    let control_frames = plan_control_frames(&value, &state, Path::new(&outputs_folder), timestamp)?;
 
     // Build CLI arguments for video
-    let args = build_video_args(&value, &state, &output_avi_path)?;
+    let args = build_video_args(
+        &value,
+        &state,
+        &output_avi_path,
+        control_frames.as_ref().map(ControlFrames::dir),
+    )?;
+
+    if value["dryRun"].as_bool() == Some(true) {
+        return Ok(serde_json::json!({
+            "success": true,
+            "dryRun": true,
+            "args": args,
+            "commandLine": format!("{} {}", exe_path.display(), quote_args(&args)),
+            "effectiveParams": &value,
+        }));
+    }
+
+    // Frames are only extracted for real runs; the guard removes them when generation ends
+    let _control_frames_guard = match &control_frames {
+        Some(ControlFrames::Extract { video, dir, ffmpeg }) => {
+            Some(extract_control_frames(&value, video, dir, ffmpeg).await?)
+        }
+        _ => None,
+    };
 
     // Setup cancellation
     let (cancel_tx, mut cancel_rx) = tokio::sync::watch::channel(false);
@@ -172,37 +194,56 @@ pub async fn generate_video_start(
         }));
     }
 
-    // Convert AVI to MP4 using FFmpeg
-    let ffmpeg_path = state::get_ffmpeg_path();
+    // Transcode the AVI with the chosen encoding profile
     let mut final_video_path = output_avi_path.clone();
-
-    if output_avi_path.exists() && ffmpeg_path.exists() {
-        let mut ffmpeg_cmd = tokio::process::Command::new(&ffmpeg_path);
-        ffmpeg_cmd.args(&[
-            "-i",
-            &output_avi_path.to_string_lossy(),
-            "-c:v",
-            "libx264",
-            "-preset",
-            "fast",
-            "-crf",
-            "23",
-            "-y",
-            &output_mp4_path.to_string_lossy(),
-        ]);
-        configure_command(&mut ffmpeg_cmd);
-        let ffmpeg_result = ffmpeg_cmd.output().await;
-
-        if let Ok(output) = ffmpeg_result {
-            if output.status.success() && output_mp4_path.exists() {
-                let _ = tokio::fs::remove_file(&output_avi_path).await;
-                final_video_path = output_mp4_path.clone();
+    // A kept AVI gets its own stem so it doesn't share the encoded video's sidecar
+    let mut kept_avi_path: Option<PathBuf> = None;
+    let encode_outcome = match resolve_ffmpeg_path(&state) {
+        _ if !output_avi_path.exists() => None,
+        Err(e) => Some(serde_json::json!({ "success": false, "error": e })),
+        Ok(ffmpeg_path) => {
+            let mut ffmpeg_args: Vec<String> = Vec::new();
+            // Read the AVI at the requested frame rate so playback speed matches
+            if let Some(fps) = video_fps(&value) {
+                ffmpeg_args.extend(["-r".to_string(), fps.to_string()]);
             }
+            ffmpeg_args.extend(["-i".to_string(), output_avi_path.to_string_lossy().to_string()]);
+            ffmpeg_args.extend(encode_args.iter().cloned());
+            ffmpeg_args.extend(["-an".to_string(), "-y".to_string()]);
+            ffmpeg_args.push(encoded_path.to_string_lossy().to_string());
+
+            let outcome = match run_ffmpeg(&ffmpeg_path, &ffmpeg_args).await {
+                Ok(outcome) => outcome,
+                Err(e) => FfmpegOutcome {
+                    success: false,
+                    exit_code: None,
+                    stderr: e,
+                },
+            };
+            if outcome.success && encoded_path.exists() {
+                if encode_profile.keep_avi {
+                    let kept = Path::new(&outputs_folder).join(format!("generated_{}_original.avi", timestamp));
+                    if tokio::fs::rename(&output_avi_path, &kept).await.is_ok() {
+                        kept_avi_path = Some(kept);
+                    }
+                } else {
+                    let _ = tokio::fs::remove_file(&output_avi_path).await;
+                }
+                final_video_path = encoded_path.clone();
+            } else {
+                let _ = tokio::fs::remove_file(&encoded_path).await;
+            }
+            Some(serde_json::to_value(&outcome).unwrap_or_default())
         }
-    }
+    };
 
     let duration = start.elapsed().as_millis() as u64;
 
+    let models = collect_model_files(&args).await;
+    let engine = describe_engine(device_type, &device_folder, &exe_path).await;
+    let group_name = lookup_group_name(&state, value["groupId"].as_str());
+    let command_line = format!("{} {}", exe_path.display(), quote_args(&args));
+
     // Save metadata
     let metadata_path = final_video_path.with_extension("json");
     let metadata = serde_json::json!({
@@ -214,11 +255,29 @@ pub async fn generate_video_start(
         "height": value.get("height"),
         "seed": value.get("seed"),
         "mode": mode,
+        "videoFrames": video_frames(&value),
+        "fps": video_fps(&value),
+        "initImage": value.get("initImage"),
+        "endImage": value.get("endImage"),
+        "controlVideo": value.get("controlVideo"),
         "deviceType": device_type,
         "groupId": value.get("groupId"),
+        "groupName": group_name,
+        "modelPath": primary_model_path(&models),
+        "vaeModelPath": models.get("vaeModel").map(|m| &m.path),
+        "llmModelPath": models.get("llmModel").map(|m| &m.path),
+        "models": models,
+        "engine": engine,
+        "embeddings": prompt_embeddings(&value, &state),
+        "encoding": &encode_profile,
+        "encodeResult": &encode_outcome,
+        "originalVideoPath": kept_avi_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+        "commandLine": command_line,
         "type": "video",
         "mediaType": "video",
         "duration": duration,
+        "request": &request,
+        "effectiveParams": &value,
         "generatedAt": chrono::Utc::now().to_rfc3339(),
     });
     let _ = tokio::fs::write(
@@ -226,6 +285,16 @@ pub async fn generate_video_start(
         serde_json::to_string_pretty(&metadata).unwrap_or_default(),
     )
     .await;
+    if let Some(kept_avi_path) = &kept_avi_path {
+        let mut avi_metadata = metadata.clone();
+        avi_metadata["originalVideoPath"] = serde_json::Value::Null;
+        avi_metadata["encodedVideoPath"] = final_video_path.to_string_lossy().into();
+        let _ = tokio::fs::write(
+            kept_avi_path.with_extension("json"),
+            serde_json::to_string_pretty(&avi_metadata).unwrap_or_default(),
+        )
+        .await;
+    }
 
     if final_video_path.exists() {
         let video_url = format!(
@@ -237,6 +306,7 @@ pub async fn generate_video_start(
             "success": true,
             "video": video_url,
             "videoPath": final_video_path.to_string_lossy(),
+            "encodeResult": encode_outcome,
             "duration": duration
         }))
     } else {
@@ -256,10 +326,115 @@ pub async fn generate_video_cancel(state: State<'_, AppState>) -> Result<bool, S
     Ok(true)
 }
 
+/// Model slots passed to sd.cpp for video generation, as (flag, request/group key)
+const VIDEO_MODEL_SLOTS: &[(&str, &str)] = &[
+    ("--diffusion-model", "diffusionModel"),
+    ("-m", "sdModel"),
+    ("--high-noise-diffusion-model", "highNoiseSdModel"),
+    ("--vae", "vaeModel"),
+    ("--llm", "llmModel"),
+    ("--clip_l", "clipLModel"),
+    ("--t5xxl", "t5xxlModel"),
+    ("--clip-vision", "clipVisionModel"),
+    ("--taesd", "taesdModel"),
+];
+
+/// Frame count from `videoFrames` (workflow node) or `frames`
+fn video_frames(params: &serde_json::Value) -> Option<u64> {
+    params["videoFrames"]
+        .as_u64()
+        .or_else(|| params["frames"].as_u64())
+        .filter(|frames| *frames > 0)
+}
+
+fn video_fps(params: &serde_json::Value) -> Option<u64> {
+    params["fps"].as_u64().filter(|fps| *fps > 0)
+}
+
+/// Source of the `--control-video` frames
+enum ControlFrames {
+    /// A directory of frames, used as is
+    Dir(PathBuf),
+    /// A video file to split into PNG frames in a per-job directory
+    Extract { video: PathBuf, dir: PathBuf, ffmpeg: PathBuf },
+}
+
+impl ControlFrames {
+    fn dir(&self) -> &Path {
+        match self {
+            ControlFrames::Dir(dir) | ControlFrames::Extract { dir, .. } => dir,
+        }
+    }
+}
+
+/// Resolve `controlVideo` to the frame directory for `--control-video` without touching the
+/// disk, so dry runs can report the arguments without extracting anything
+fn plan_control_frames(
+    params: &serde_json::Value,
+    state: &AppState,
+    outputs_folder: &Path,
+    timestamp: u128,
+) -> Result<Option<ControlFrames>, String> {
+    let Some(control) = params["controlVideo"].as_str().filter(|p| !p.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let control_path = Path::new(control);
+    if control_path.is_dir() {
+        return Ok(Some(ControlFrames::Dir(control_path.to_path_buf())));
+    }
+    if !control_path.is_file() {
+        return Err(format!("控制视频不存在：{}", control));
+    }
+
+    let ffmpeg = resolve_ffmpeg_path(state).map_err(|e| format!("控制视频需要 FFmpeg 拆分帧：{}", e))?;
+    Ok(Some(ControlFrames::Extract {
+        video: control_path.to_path_buf(),
+        dir: outputs_folder.join(".control").join(timestamp.to_string()),
+        ffmpeg,
+    }))
+}
+
+/// Split a control video into PNG frames in `dir`, which is removed when the returned guard drops
+async fn extract_control_frames(
+    params: &serde_json::Value,
+    video: &Path,
+    dir: &Path,
+    ffmpeg_path: &Path,
+) -> Result<JobTempDir, String> {
+    let dir = JobTempDir::create(dir.to_path_buf())?;
+
+    let mut ffmpeg_cmd = tokio::process::Command::new(ffmpeg_path);
+    ffmpeg_cmd.arg("-i").arg(video);
+    if let Some(fps) = video_fps(params) {
+        ffmpeg_cmd.args(["-vf", &format!("fps={}", fps)]);
+    }
+    if let Some(frames) = video_frames(params) {
+        ffmpeg_cmd.args(["-frames:v", &frames.to_string()]);
+    }
+    ffmpeg_cmd.arg("-y").arg(dir.path().join("%05d.png"));
+    configure_command(&mut ffmpeg_cmd);
+    let output = ffmpeg_cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        return Err(format!(
+            "控制视频拆分帧失败（FFmpeg exit code {:?}）：{}",
+            output.status.code(),
+            tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+        ));
+    }
+
+    Ok(dir)
+}
+
 fn build_video_args(
     params: &serde_json::Value,
     state: &State<'_, AppState>,
     output_path: &Path,
+    control_frames_dir: Option<&Path>,
 ) -> Result<Vec<String>, String> {
     let mut args = Vec::new();
 
@@ -270,53 +445,66 @@ fn build_video_args(
         .clone()
         .unwrap_or_default();
 
-    // Model paths from group
-    if let Some(group_id) = params["groupId"].as_str() {
-        let groups_path = state::get_model_groups_path(Some(&weights_folder));
-        if groups_path.exists() {
-            let data = std::fs::read_to_string(&groups_path).map_err(|e| e.to_string())?;
-            let groups: Vec<serde_json::Value> =
-                serde_json::from_str(&data).map_err(|e| e.to_string())?;
-            if let Some(group) = groups.iter().find(|g| g["id"].as_str() == Some(group_id)) {
-                if let Some(sd_model) = group["sdModel"].as_str() {
-                    let resolved = state::resolve_model_path(sd_model, &weights_folder);
-                    args.push("-m".to_string());
-                    args.push(resolved);
-                }
-                if let Some(vae) = group["vaeModel"].as_str() {
-                    if !vae.is_empty() {
-                        args.push("--vae".to_string());
-                        args.push(state::resolve_model_path(vae, &weights_folder));
-                    }
-                }
-                if let Some(llm) = group["llmModel"].as_str() {
-                    if !llm.is_empty() {
-                        args.push("--clip_l".to_string());
-                        args.push(state::resolve_model_path(llm, &weights_folder));
-                    }
-                }
-                if let Some(clip_vision) = group["clipVisionModel"].as_str() {
-                    if !clip_vision.is_empty() {
-                        args.push("--clip-vision".to_string());
-                        args.push(state::resolve_model_path(clip_vision, &weights_folder));
-                    }
-                }
-                if let Some(t5xxl) = group["t5xxlModel"].as_str() {
-                    if !t5xxl.is_empty() {
-                        args.push("--t5xxl".to_string());
-                        args.push(state::resolve_model_path(t5xxl, &weights_folder));
-                    }
-                }
-                if let Some(high_noise) = group["highNoiseSdModel"].as_str() {
-                    if !high_noise.is_empty() {
-                        args.push("--high-noise-model".to_string());
-                        args.push(state::resolve_model_path(high_noise, &weights_folder));
-                    }
-                }
+    let group: Option<serde_json::Value> = match params["groupId"].as_str() {
+        Some(group_id) if !group_id.trim().is_empty() => {
+            let groups_path = state::get_model_groups_path(Some(&weights_folder));
+            if groups_path.exists() {
+                let data = std::fs::read_to_string(&groups_path).map_err(|e| e.to_string())?;
+                let groups: Vec<serde_json::Value> =
+                    serde_json::from_str(&data).map_err(|e| e.to_string())?;
+                groups.into_iter().find(|g| g["id"].as_str() == Some(group_id))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+    let group_folder = group
+        .as_ref()
+        .and_then(|g| g["folder"].as_str())
+        .filter(|f| !f.trim().is_empty());
+
+    // 请求中显式指定了主模型时使用请求中的模型路径，否则使用模型组配置
+    let explicit_models = ["diffusionModel", "sdModel"]
+        .iter()
+        .any(|key| params[*key].as_str().is_some_and(|p| !p.trim().is_empty()));
+    let model_source = if explicit_models {
+        Some(params)
+    } else {
+        group.as_ref()
+    };
+
+    let mut resolved_diffusion_model: Option<String> = None;
+    let mut resolved_sd_model: Option<String> = None;
+
+    // Model paths (Wan 等视频模型使用 --diffusion-model + --llm，不能再追加 -m)
+    if let Some(source) = model_source {
+        for (flag, key) in VIDEO_MODEL_SLOTS {
+            let Some(model_path) = source[*key].as_str().filter(|p| !p.trim().is_empty()) else {
+                continue;
+            };
+            let resolved = resolve_generate_model_path(model_path, &weights_folder, group_folder);
+            match *key {
+                "diffusionModel" => resolved_diffusion_model = Some(resolved.clone()),
+                "sdModel" => resolved_sd_model = Some(resolved.clone()),
+                _ => {}
             }
+            args.push(flag.to_string());
+            args.push(resolved);
         }
     }
 
+    let has_diffusion_model = resolved_diffusion_model
+        .as_ref()
+        .is_some_and(|p| !p.trim().is_empty() && Path::new(p).exists());
+    let has_sd_model = resolved_sd_model
+        .as_ref()
+        .is_some_and(|p| !p.trim().is_empty() && Path::new(p).exists());
+
+    if !has_diffusion_model && !has_sd_model {
+        return Err("未找到可用模型文件：请检查模型组中的 diffusionModel/sdModel 是否已配置且文件存在".to_string());
+    }
+
     // Mode
     let mode = params["mode"].as_str().unwrap_or("text2video");
     args.push("--mode".to_string());
@@ -338,6 +526,12 @@ fn build_video_args(
         }
     }
 
+    // Textual inversion embeddings
+    if let Some(embd_dir) = embeddings_dir_arg(state) {
+        args.push("--embd-dir".to_string());
+        args.push(embd_dir);
+    }
+
     // Dimensions
     if let Some(w) = params["width"].as_u64() {
         args.push("-W".to_string());
@@ -374,6 +568,14 @@ fn build_video_args(
         }
     }
 
+    // Scheduler
+    if let Some(sched) = params["scheduler"].as_str() {
+        if !sched.is_empty() {
+            args.push("--scheduler".to_string());
+            args.push(sched.to_string());
+        }
+    }
+
     // Flow shift
     if let Some(flow) = params["flowShift"].as_f64() {
         args.push("--flow-shift".to_string());
@@ -396,12 +598,36 @@ fn build_video_args(
         }
     }
 
+    // Frame count and frame rate
+    if let Some(frames) = video_frames(params) {
+        args.push("--video-frames".to_string());
+        args.push(frames.to_string());
+    }
+    if let Some(fps) = video_fps(params) {
+        args.push("--fps".to_string());
+        args.push(fps.to_string());
+    }
+
     // Init image for i2v
-    if let Some(init) = params["initImage"].as_str() {
-        if !init.is_empty() {
-            args.push("-i".to_string());
-            args.push(init.to_string());
+    let init_image = params["initImage"].as_str().filter(|p| !p.is_empty());
+    if let Some(init) = init_image {
+        args.push("-i".to_string());
+        args.push(init.to_string());
+    }
+
+    // End image for first/last-frame (FLF2V)
+    if let Some(end) = params["endImage"].as_str().filter(|p| !p.is_empty()) {
+        if init_image.is_none() {
+            return Err("首尾帧模式需要同时提供 initImage 与 endImage".to_string());
         }
+        args.push("--end-img".to_string());
+        args.push(end.to_string());
+    }
+
+    // Control video frames (e.g. Wan VACE)
+    if let Some(dir) = control_frames_dir {
+        args.push("--control-video".to_string());
+        args.push(dir.to_string_lossy().to_string());
     }
 
     // Boolean flags
@@ -420,6 +646,9 @@ fn build_video_args(
     if params["vaeTiling"].as_bool() == Some(true) {
         args.push("--vae-tiling".to_string());
     }
+    if params["taesdPreviewOnly"].as_bool() == Some(true) {
+        args.push("--taesd-preview-only".to_string());
+    }
 
     // Output
     args.push("-o".to_string());
@@ -0,0 +1,284 @@
+use crate::state::{self, AppState, CancelGuard};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter, State};
+
+use super::derived_media::{
+    encode_failure, extend_metadata, inherited_metadata, new_output_path, read_sidecar, video_result,
+    write_sidecar,
+};
+use super::ffmpeg::{probe_video, resolve_ffmpeg_path, run_ffmpeg, VideoEncodeProfile};
+use super::generate::{
+    configure_command, resolve_generate_model_path, resolve_sdcpp_executable, JobTempDir,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoPostprocessRequest {
+    /// Source gallery video
+    pub path: String,
+    /// Interpolate to this frame rate with FFmpeg `minterpolate`
+    pub target_fps: Option<f64>,
+    /// ESRGAN model for per-frame upscaling (absolute or relative to the weights folder)
+    pub upscale_model: Option<String>,
+    pub upscale_repeats: Option<u32>,
+    pub device_type: Option<String>,
+    pub threads: Option<u32>,
+    pub encoding: Option<VideoEncodeProfile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoPostprocessProgress {
+    /// "extracting", "upscaling", "encoding" or "done"
+    pub stage: String,
+    pub current: Option<u64>,
+    pub total: Option<u64>,
+    pub text: String,
+}
+
+/// Interpolate and/or upscale a gallery video, saving the result as a new gallery item
+#[tauri::command]
+pub async fn video_postprocess(
+    value: VideoPostprocessRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let source = PathBuf::from(&value.path);
+    if !source.is_file() {
+        return Err(format!("File not found: {}", value.path));
+    }
+    let upscale_model = value.upscale_model.clone().filter(|m| !m.trim().is_empty());
+    if value.target_fps.is_none() && upscale_model.is_none() {
+        return Err("Nothing to do: set targetFps and/or upscaleModel".to_string());
+    }
+    if value.target_fps.is_some_and(|fps| fps <= 0.0) {
+        return Err("targetFps must be greater than 0".to_string());
+    }
+
+    let ffmpeg_path = resolve_ffmpeg_path(&state)?;
+    let encode_profile = value.encoding.clone().unwrap_or_default();
+    let (encode_args, encoded_ext) = encode_profile.encode_args()?;
+
+    let source_meta = read_sidecar(&source);
+    let source_fps = match source_meta["fps"].as_f64() {
+        Some(fps) if fps > 0.0 => fps,
+        _ => probe_video(&ffmpeg_path, &source)
+            .await
+            .and_then(|probe| probe.fps)
+            .unwrap_or(16.0),
+    };
+
+    let (output_path, timestamp) = new_output_path(&state, encoded_ext);
+    let work_dir = JobTempDir::create(
+        state::get_outputs_folder(&state)
+            .join(".postprocess")
+            .join(timestamp.to_string()),
+    )?;
+
+    let (cancel_tx, mut cancel_rx) = tokio::sync::watch::channel(false);
+    let _cancel_guard = CancelGuard::install(&state.postprocess_cancel, cancel_tx);
+    let start = std::time::Instant::now();
+
+    // Input for the final encode: the source video, or the upscaled frame sequence
+    let mut input_args: Vec<String> = vec!["-i".to_string(), value.path.clone()];
+    let mut resolved_upscale_model: Option<String> = None;
+    if let Some(model) = &upscale_model {
+        let weights_folder = state
+            .weights_folder
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| state::get_default_models_folder().to_string_lossy().to_string());
+        let model_path = resolve_generate_model_path(model, &weights_folder, None);
+        if !Path::new(&model_path).is_file() {
+            return Err(format!("Upscale model not found: {}", model));
+        }
+
+        let sdcpp_folder = state
+            .sdcpp_folder
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("SD.cpp folder not set")?;
+        let device_type = value
+            .device_type
+            .clone()
+            .filter(|d| !d.trim().is_empty())
+            .unwrap_or_else(|| state.sdcpp_device_type.lock().unwrap().clone());
+        let device_folder = Path::new(&sdcpp_folder).join(&device_type);
+        let exe_path = resolve_sdcpp_executable(&device_folder).ok_or_else(|| {
+            format!(
+                "SD.cpp executable not found in: {} (expected one of: sd/sd-cli/sd_server/sd-server)",
+                device_folder.display()
+            )
+        })?;
+
+        let frames_in = work_dir.path().join("in");
+        let frames_out = work_dir.path().join("out");
+        std::fs::create_dir_all(&frames_in).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&frames_out).map_err(|e| e.to_string())?;
+
+        emit_progress(&app, "extracting", None, None, &value.path);
+        let extract_args = [
+            "-i".to_string(),
+            value.path.clone(),
+            "-y".to_string(),
+            frames_in.join("%05d.png").to_string_lossy().to_string(),
+        ];
+        tokio::select! {
+            outcome = run_ffmpeg(&ffmpeg_path, &extract_args) => outcome?.into_result()?,
+            _ = cancel_rx.changed() => {
+                return Ok(serde_json::json!({ "success": false, "error": "cancelled" }));
+            }
+        };
+
+        let mut frames: Vec<PathBuf> = std::fs::read_dir(&frames_in)
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .map(|entry| entry.path())
+            .collect();
+        frames.sort();
+        let total = frames.len() as u64;
+
+        for (index, frame) in frames.iter().enumerate() {
+            let frame_name = frame.file_name().unwrap_or_default();
+            let mut args = vec![
+                "-M".to_string(),
+                "upscale".to_string(),
+                "--upscale-model".to_string(),
+                model_path.clone(),
+                "-i".to_string(),
+                frame.to_string_lossy().to_string(),
+                "-o".to_string(),
+                frames_out.join(frame_name).to_string_lossy().to_string(),
+            ];
+            if let Some(repeats) = value.upscale_repeats.filter(|r| *r > 1) {
+                args.push("--upscale-repeats".to_string());
+                args.push(repeats.to_string());
+            }
+            if let Some(threads) = value.threads {
+                args.push("-t".to_string());
+                args.push(threads.to_string());
+            }
+
+            let mut cmd = tokio::process::Command::new(&exe_path);
+            cmd.args(&args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+            configure_command(&mut cmd);
+            let output = tokio::select! {
+                output = cmd.output() => output.map_err(|e| format!("Failed to start process: {}", e))?,
+                _ = cancel_rx.changed() => {
+                    return Ok(serde_json::json!({ "success": false, "error": "cancelled" }));
+                }
+            };
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let tail: Vec<&str> = stderr.lines().rev().take(10).collect();
+                return Err(format!(
+                    "Upscaling frame {} failed (exit code {:?}):\n{}",
+                    index + 1,
+                    output.status.code(),
+                    tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+                ));
+            }
+            emit_progress(
+                &app,
+                "upscaling",
+                Some(index as u64 + 1),
+                Some(total),
+                &frame_name.to_string_lossy(),
+            );
+        }
+
+        input_args = vec![
+            "-framerate".to_string(),
+            source_fps.to_string(),
+            "-i".to_string(),
+            frames_out.join("%05d.png").to_string_lossy().to_string(),
+        ];
+        resolved_upscale_model = Some(model_path);
+    }
+
+    // Motion-compensated interpolation to the target frame rate
+    let mut ffmpeg_args = input_args;
+    if let Some(target_fps) = value.target_fps {
+        ffmpeg_args.extend([
+            "-vf".to_string(),
+            format!(
+                "minterpolate=fps={}:mi_mode=mci:mc_mode=aobmc:me_mode=bidir:vsbmc=1",
+                target_fps
+            ),
+        ]);
+    }
+    ffmpeg_args.extend(encode_args);
+    ffmpeg_args.extend([
+        "-an".to_string(),
+        "-y".to_string(),
+        output_path.to_string_lossy().to_string(),
+    ]);
+
+    emit_progress(&app, "encoding", None, None, &output_path.to_string_lossy());
+    let outcome = tokio::select! {
+        outcome = run_ffmpeg(&ffmpeg_path, &ffmpeg_args) => outcome?,
+        _ = cancel_rx.changed() => {
+            let _ = tokio::fs::remove_file(&output_path).await;
+            return Ok(serde_json::json!({ "success": false, "error": "cancelled" }));
+        }
+    };
+    if !outcome.success || !output_path.exists() {
+        return Ok(encode_failure(&output_path, &outcome).await);
+    }
+
+    let mut metadata = inherited_metadata(&source_meta);
+    extend_metadata(
+        &mut metadata,
+        serde_json::json!({
+            "type": "postprocess",
+            "mediaType": "video",
+            "sourceVideo": value.path,
+            "fps": value.target_fps.unwrap_or(source_fps),
+            "postprocess": {
+                "sourceFps": source_fps,
+                "targetFps": value.target_fps,
+                "upscaleModel": resolved_upscale_model,
+                "upscaleRepeats": value.upscale_repeats,
+            },
+            "encoding": &encode_profile,
+            "encodeResult": &outcome,
+            "duration": start.elapsed().as_millis() as u64,
+            "generatedAt": chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+    write_sidecar(&output_path, &metadata).await;
+
+    emit_progress(&app, "done", None, None, &output_path.to_string_lossy());
+    drop(work_dir);
+
+    Ok(video_result(&output_path, start))
+}
+
+/// Cancel a running video post-processing job
+#[tauri::command]
+pub async fn video_cancel_postprocess(state: State<'_, AppState>) -> Result<bool, String> {
+    if let Some(cancel) = state.postprocess_cancel.lock().unwrap().take() {
+        let _ = cancel.send(true);
+    }
+    Ok(true)
+}
+
+fn emit_progress(app: &AppHandle, stage: &str, current: Option<u64>, total: Option<u64>, text: &str) {
+    let _ = app.emit(
+        "video:postprocess-progress",
+        VideoPostprocessProgress {
+            stage: stage.to_string(),
+            current,
+            total,
+            text: text.to_string(),
+        },
+    );
+}
@@ -0,0 +1,209 @@
+use crate::imaging;
+use crate::state::{self, AppState};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::derived_media::{encode_failure, new_output_path, video_result, write_sidecar};
+use super::ffmpeg::{resolve_ffmpeg_path, run_ffmpeg, VideoEncodeProfile};
+use super::generate::JobTempDir;
+
+/// Output frame rate for slideshows when each image is held for a duration
+const SLIDESHOW_FRAME_RATE: u32 = 25;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoSlideshowRequest {
+    /// Gallery images in playback order
+    pub images: Vec<String>,
+    /// Seconds each image is shown (slideshow mode, default 2)
+    pub image_duration: Option<f64>,
+    /// Images per second (timelapse mode); takes precedence over `image_duration`
+    pub fps: Option<f64>,
+    /// Crossfade between images in seconds; slideshow mode only
+    pub crossfade: Option<f64>,
+    /// Output size; defaults to the first image's size. Images are letterboxed to fit
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub encoding: Option<VideoEncodeProfile>,
+}
+
+/// Assemble gallery images into a slideshow or timelapse video saved as a new gallery item
+#[tauri::command]
+pub async fn video_slideshow(
+    value: VideoSlideshowRequest,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    if value.images.is_empty() {
+        return Err("No images selected".to_string());
+    }
+    if let Some(missing) = value.images.iter().find(|p| !Path::new(p).is_file()) {
+        return Err(format!("File not found: {}", missing));
+    }
+    if value.fps.is_some_and(|fps| fps <= 0.0) || value.image_duration.is_some_and(|d| d <= 0.0) {
+        return Err("fps and imageDuration must be greater than 0".to_string());
+    }
+    let crossfade = value.crossfade.filter(|c| *c > 0.0);
+    let image_duration = value.image_duration.unwrap_or(2.0);
+    if value.fps.is_some() && crossfade.is_some() {
+        return Err("crossfade is not supported in timelapse (fps) mode".to_string());
+    }
+    if crossfade.is_some_and(|c| c >= image_duration) {
+        return Err("crossfade must be shorter than imageDuration".to_string());
+    }
+
+    let (width, height) = match (value.width, value.height) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => (w, h),
+        _ => imaging::image_dimensions(Path::new(&value.images[0]))
+            .ok_or("Could not read the first image's size; set width and height")?,
+    };
+    // yuv420p needs even dimensions
+    let (width, height) = (width.max(2) & !1, height.max(2) & !1);
+
+    let ffmpeg_path = resolve_ffmpeg_path(&state)?;
+    let encode_profile = value.encoding.clone().unwrap_or_default();
+    let (encode_args, encoded_ext) = encode_profile.encode_args()?;
+
+    let (output_path, timestamp) = new_output_path(&state, encoded_ext);
+    let fit = format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2:color=black,setsar=1",
+        w = width,
+        h = height
+    );
+    let start = std::time::Instant::now();
+
+    let mut ffmpeg_args: Vec<String> = Vec::new();
+    // Kept alive until the encode finishes; holds the concat list in timelapse mode
+    let mut _work_dir: Option<JobTempDir> = None;
+    let (frame_rate, video_length) = if let Some(fps) = value.fps {
+        // Timelapse: one image per frame through the concat demuxer, so long sequences
+        // don't need an input (and a decoder) per image
+        let work_dir = JobTempDir::create(
+            state::get_outputs_folder(&state)
+                .join(".slideshow")
+                .join(timestamp.to_string()),
+        )?;
+        let list_path = work_dir.path().join("images.txt");
+        std::fs::write(&list_path, concat_list(&value.images, 1.0 / fps)).map_err(|e| e.to_string())?;
+        ffmpeg_args.extend([
+            "-f".to_string(),
+            "concat".to_string(),
+            "-safe".to_string(),
+            "0".to_string(),
+            "-i".to_string(),
+            list_path.to_string_lossy().to_string(),
+            "-vf".to_string(),
+            format!("{},fps={},format=yuv420p", fit, fps),
+            "-r".to_string(),
+            fps.to_string(),
+        ]);
+        _work_dir = Some(work_dir);
+        (fps, value.images.len() as f64 / fps)
+    } else {
+        let frame_rate = SLIDESHOW_FRAME_RATE;
+        let mut graph: Vec<String> = Vec::new();
+        for (index, image) in value.images.iter().enumerate() {
+            ffmpeg_args.extend([
+                "-loop".to_string(),
+                "1".to_string(),
+                "-t".to_string(),
+                image_duration.to_string(),
+                "-i".to_string(),
+                image.clone(),
+            ]);
+            graph.push(format!(
+                "[{i}:v]{fit},fps={r},format=yuv420p[v{i}]",
+                i = index,
+                fit = fit,
+                r = frame_rate
+            ));
+        }
+
+        let count = value.images.len();
+        match crossfade {
+            Some(fade) if count > 1 => {
+                // Each transition overlaps the previous clip by `fade`, so the k-th one starts
+                // at k * (duration - fade)
+                let mut previous = "v0".to_string();
+                for index in 1..count {
+                    let label = if index == count - 1 { "out".to_string() } else { format!("x{}", index) };
+                    graph.push(format!(
+                        "[{}][v{}]xfade=transition=fade:duration={}:offset={}[{}]",
+                        previous,
+                        index,
+                        fade,
+                        index as f64 * (image_duration - fade),
+                        label
+                    ));
+                    previous = label;
+                }
+            }
+            _ => {
+                let inputs: String = (0..count).map(|i| format!("[v{}]", i)).collect();
+                graph.push(format!("{}concat=n={}:v=1:a=0[out]", inputs, count));
+            }
+        }
+        ffmpeg_args.extend([
+            "-filter_complex".to_string(),
+            graph.join(";"),
+            "-map".to_string(),
+            "[out]".to_string(),
+        ]);
+        let length = count as f64 * image_duration - crossfade.unwrap_or(0.0) * (count as f64 - 1.0);
+        (frame_rate as f64, length)
+    };
+    ffmpeg_args.extend(encode_args);
+    ffmpeg_args.extend([
+        "-an".to_string(),
+        "-y".to_string(),
+        output_path.to_string_lossy().to_string(),
+    ]);
+
+    let outcome = run_ffmpeg(&ffmpeg_path, &ffmpeg_args).await?;
+    drop(_work_dir);
+    if !outcome.success || !output_path.exists() {
+        return Ok(encode_failure(&output_path, &outcome).await);
+    }
+
+    let metadata = serde_json::json!({
+        "type": "slideshow",
+        "mediaType": "video",
+        "sourceImages": &value.images,
+        "width": width,
+        "height": height,
+        "fps": frame_rate,
+        "videoFrames": (video_length * frame_rate).round() as u64,
+        "slideshow": {
+            "mode": if value.fps.is_some() { "timelapse" } else { "slideshow" },
+            "imageDuration": if value.fps.is_some() { None } else { Some(image_duration) },
+            "imagesPerSecond": value.fps,
+            "crossfade": crossfade,
+            "length": video_length,
+        },
+        "encoding": &encode_profile,
+        "encodeResult": &outcome,
+        "duration": start.elapsed().as_millis() as u64,
+        "generatedAt": chrono::Utc::now().to_rfc3339(),
+    });
+    write_sidecar(&output_path, &metadata).await;
+
+    Ok(video_result(&output_path, start))
+}
+
+/// Concat demuxer script showing each image for `frame_duration` seconds. The last entry is
+/// repeated because the demuxer ignores the duration of the final file
+fn concat_list(images: &[String], frame_duration: f64) -> String {
+    let entry = |path: &str| {
+        let absolute = dunce::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        format!("file '{}'\n", absolute.to_string_lossy().replace('\'', "'\\''"))
+    };
+    let mut list = String::from("ffconcat version 1.0\n");
+    for image in images {
+        list.push_str(&entry(image));
+        list.push_str(&format!("duration {}\n", frame_duration));
+    }
+    if let Some(last) = images.last() {
+        list.push_str(&entry(last));
+    }
+    list
+}
@@ -1,9 +1,11 @@
 use crate::state::{self, AppState};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::State;
 use tauri_plugin_dialog::DialogExt;
 
+use super::trash::{move_to_trash, TrashKind, TRASH_DIR_NAME};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WeightFile {
@@ -76,6 +78,9 @@ fn list_files_recursive(
         let path = entry.path();
 
         if path.is_dir() {
+            if entry.file_name() == TRASH_DIR_NAME {
+                continue;
+            }
             list_files_recursive(&path, base, files)?;
         } else {
             let metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
@@ -148,14 +153,9 @@ pub async fn weights_download_file(value: String, app: tauri::AppHandle) -> Resu
     }
 }
 
-/// Delete a weight file
+/// Move a weight file to the recycle bin
 #[tauri::command]
-pub async fn weights_delete_file(value: String) -> Result<bool, String> {
-    let path = Path::new(&value);
-    if path.exists() {
-        std::fs::remove_file(path).map_err(|e| e.to_string())?;
-        Ok(true)
-    } else {
-        Ok(false)
-    }
+pub async fn weights_delete_file(value: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let trashed = move_to_trash(&state, TrashKind::Weights, &[PathBuf::from(&value)], serde_json::Value::Null)?;
+    Ok(trashed.is_some())
 }
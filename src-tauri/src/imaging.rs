@@ -0,0 +1,260 @@
+//! Minimal RGBA image helpers built on the `png` crate.
+//!
+//! Generated outputs are PNG, so this covers the gallery-side image work
+//! (canvas building, masks, tiling, thumbnails) without pulling in a full imaging stack.
+//! JPEG/WebP support is limited to what gallery thumbnails need.
+
+use std::io::BufWriter;
+use std::path::Path;
+
+/// An 8-bit RGBA image in row-major order
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Create an image filled with a single color
+    pub fn new(width: u32, height: u32, fill: [u8; 4]) -> Self {
+        let data = fill
+            .iter()
+            .copied()
+            .cycle()
+            .take(width as usize * height as usize * 4)
+            .collect();
+        Self { width, height, data }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.data[i], self.data[i + 1], self.data[i + 2], self.data[i + 3]]
+    }
+
+    pub fn put(&mut self, x: u32, y: u32, pixel: [u8; 4]) {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.data[i..i + 4].copy_from_slice(&pixel);
+    }
+
+    /// Repeat the image `cols` x `rows` times
+    pub fn tile(&self, cols: u32, rows: u32) -> Self {
+        let row_bytes = self.width as usize * 4;
+        let mut data = Vec::with_capacity(self.data.len() * cols as usize * rows as usize);
+        for _ in 0..rows {
+            for row in self.data.chunks_exact(row_bytes) {
+                for _ in 0..cols {
+                    data.extend_from_slice(row);
+                }
+            }
+        }
+        Self {
+            width: self.width * cols,
+            height: self.height * rows,
+            data,
+        }
+    }
+
+    /// Downscale so the longer side is at most `max_side`, averaging the source pixels each
+    /// output pixel covers. Images that already fit are returned unchanged
+    pub fn resize_to_fit(&self, max_side: u32) -> Self {
+        let longer = self.width.max(self.height);
+        if longer <= max_side || longer == 0 {
+            return self.clone();
+        }
+        let scale = max_side as f64 / longer as f64;
+        let width = ((self.width as f64 * scale).round() as u32).max(1);
+        let height = ((self.height as f64 * scale).round() as u32).max(1);
+
+        let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            let y0 = (y as u64 * self.height as u64 / height as u64) as u32;
+            let y1 = (((y as u64 + 1) * self.height as u64).div_ceil(height as u64) as u32).max(y0 + 1);
+            for x in 0..width {
+                let x0 = (x as u64 * self.width as u64 / width as u64) as u32;
+                let x1 = (((x as u64 + 1) * self.width as u64).div_ceil(width as u64) as u32).max(x0 + 1);
+                let mut sum = [0u64; 4];
+                for sy in y0..y1.min(self.height) {
+                    for sx in x0..x1.min(self.width) {
+                        let pixel = self.get(sx, sy);
+                        for (total, channel) in sum.iter_mut().zip(pixel) {
+                            *total += channel as u64;
+                        }
+                    }
+                }
+                let count = ((y1.min(self.height) - y0) * (x1.min(self.width) - x0)).max(1) as u64;
+                data.extend(sum.iter().map(|total| (total / count) as u8));
+            }
+        }
+        Self { width, height, data }
+    }
+}
+
+/// Decode a PNG, JPEG or WebP file (by extension) into RGBA8
+pub fn read_image(path: &Path) -> Result<RgbaImage, String> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" => read_jpeg(path),
+        "webp" => read_webp(path),
+        _ => read_png(path),
+    }
+}
+
+/// Width and height of a PNG, JPEG or WebP file (by extension) from its header, without
+/// decoding the pixels
+pub fn image_dimensions(path: &Path) -> Option<(u32, u32)> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let reader = std::io::BufReader::new(std::fs::File::open(path).ok()?);
+    match ext.as_str() {
+        "jpg" | "jpeg" => {
+            let mut decoder = jpeg_decoder::Decoder::new(reader);
+            decoder.read_info().ok()?;
+            let info = decoder.info()?;
+            Some((info.width as u32, info.height as u32))
+        }
+        "webp" => image_webp::WebPDecoder::new(reader).ok().map(|decoder| decoder.dimensions()),
+        _ => {
+            let reader = png::Decoder::new(reader).read_info().ok()?;
+            let info = reader.info();
+            Some((info.width, info.height))
+        }
+    }
+}
+
+/// Decode a baseline or progressive JPEG into RGBA8
+pub fn read_jpeg(path: &Path) -> Result<RgbaImage, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut decoder = jpeg_decoder::Decoder::new(std::io::BufReader::new(file));
+    let pixels = decoder
+        .decode()
+        .map_err(|e| format!("Failed to read JPEG {}: {}", path.display(), e))?;
+    let info = decoder.info().ok_or("Missing JPEG header")?;
+
+    let data = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::L8 => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        jpeg_decoder::PixelFormat::L16 => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => pixels
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let k = 255 - p[3] as u32;
+                let channel = |c: u8| ((255 - c as u32) * k / 255) as u8;
+                [channel(p[0]), channel(p[1]), channel(p[2]), 255]
+            })
+            .collect(),
+    };
+
+    Ok(RgbaImage {
+        width: info.width as u32,
+        height: info.height as u32,
+        data,
+    })
+}
+
+/// Decode a still WebP (or the first frame of an animated one) into RGBA8
+pub fn read_webp(path: &Path) -> Result<RgbaImage, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut decoder = image_webp::WebPDecoder::new(std::io::BufReader::new(file))
+        .map_err(|e| format!("Failed to read WebP {}: {}", path.display(), e))?;
+    let (width, height) = decoder.dimensions();
+    let size = decoder.output_buffer_size().ok_or("WebP image too large")?;
+    let mut pixels = vec![0u8; size];
+    decoder.read_image(&mut pixels).map_err(|e| e.to_string())?;
+
+    let data = if decoder.has_alpha() {
+        pixels
+    } else {
+        pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect()
+    };
+    Ok(RgbaImage { width, height, data })
+}
+
+/// Decode a PNG file into RGBA8, expanding palette/gray images and stripping 16-bit depth
+pub fn read_png(path: &Path) -> Result<RgbaImage, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut decoder = png::Decoder::new(std::io::BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| format!("Failed to read PNG {}: {}", path.display(), e))?;
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+    let pixels = &buf[..info.buffer_size()];
+
+    let data = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => {
+            return Err(format!("Unsupported indexed PNG: {}", path.display()));
+        }
+    };
+
+    Ok(RgbaImage {
+        width: info.width,
+        height: info.height,
+        data,
+    })
+}
+
+/// Encode an RGBA8 image as PNG
+pub fn write_png(path: &Path, image: &RgbaImage) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer
+        .write_image_data(&image.data)
+        .map_err(|e| format!("Failed to write PNG {}: {}", path.display(), e))
+}
+
+/// Encode an RGBA8 image as JPEG; transparent areas are composited over white
+pub fn write_jpeg(path: &Path, image: &RgbaImage, quality: u8) -> Result<(), String> {
+    let width = u16::try_from(image.width).map_err(|_| "Image too large for JPEG")?;
+    let height = u16::try_from(image.height).map_err(|_| "Image too large for JPEG")?;
+    let rgb: Vec<u8> = image
+        .data
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let alpha = p[3] as u32;
+            let blend = |c: u8| ((c as u32 * alpha + 255 * (255 - alpha)) / 255) as u8;
+            [blend(p[0]), blend(p[1]), blend(p[2])]
+        })
+        .collect();
+    let encoder = jpeg_encoder::Encoder::new_file(path, quality)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    encoder
+        .encode(&rgb, width, height, jpeg_encoder::ColorType::Rgb)
+        .map_err(|e| format!("Failed to write JPEG {}: {}", path.display(), e))
+}
+
+/// Encode an RGBA8 image as lossless WebP, keeping transparency
+pub fn write_webp(path: &Path, image: &RgbaImage) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    image_webp::WebPEncoder::new(BufWriter::new(file))
+        .encode(&image.data, image.width, image.height, image_webp::ColorType::Rgba8)
+        .map_err(|e| format!("Failed to write WebP {}: {}", path.display(), e))
+}
@@ -1,5 +1,7 @@
 mod commands;
+mod imaging;
 mod state;
+mod watcher;
 
 use state::AppState;
 use tauri::Manager;
@@ -49,6 +51,14 @@ pub fn run() {
             commands::sdcpp::sdcpp_remove_mirror,
             commands::sdcpp::sdcpp_test_mirrors,
             commands::sdcpp::sdcpp_auto_select_mirror,
+            // ffmpeg engine
+            commands::ffmpeg::ffmpeg_detect,
+            commands::ffmpeg::ffmpeg_get_info,
+            commands::ffmpeg::ffmpeg_get_path,
+            commands::ffmpeg::ffmpeg_set_path,
+            commands::ffmpeg::ffmpeg_download,
+            commands::ffmpeg::ffmpeg_cancel_download,
+            commands::ffmpeg::ffmpeg_import,
             // model groups
             commands::model_groups::model_groups_list,
             commands::model_groups::model_groups_create,
@@ -69,29 +79,80 @@ pub fn run() {
             commands::model_download::models_verify_file,
             commands::model_download::models_delete_file,
             commands::model_download::models_clear_verified,
+            // model convert
+            commands::model_convert::models_convert,
+            commands::model_convert::models_cancel_convert,
+            // embeddings
+            commands::embeddings::embeddings_get_folder,
+            commands::embeddings::embeddings_set_folder,
+            commands::embeddings::embeddings_list,
             // generate
             commands::generate::generate_start,
             commands::generate::generate_cancel,
+            commands::outpaint::generate_outpaint,
             // video generate
             commands::video_generate::generate_video_start,
             commands::video_generate::generate_video_cancel,
+            // video editing
+            commands::video_edit::video_trim,
+            commands::video_edit::video_concat,
+            commands::video_edit::video_extract_frame,
+            // video export
+            commands::video_export::video_export,
+            // video post-processing
+            commands::video_postprocess::video_postprocess,
+            commands::video_postprocess::video_cancel_postprocess,
+            // video slideshow
+            commands::video_slideshow::video_slideshow,
             // generated images
             commands::generated_images::generated_images_list,
+            commands::generated_images::generated_images_get_outputs_folder,
+            commands::generated_images::generated_images_set_outputs_folder,
+            commands::generated_images::generated_images_get_roots,
+            commands::generated_images::generated_images_set_roots,
+            commands::generated_images::generated_images_annotate,
+            commands::generated_images::generated_images_list_tags,
+            commands::gallery_index::gallery_search,
             commands::generated_images::generated_images_download,
             commands::generated_images::generated_images_delete,
             commands::generated_images::generated_images_get_preview,
             commands::generated_images::generated_images_get_video_data,
             commands::generated_images::generated_images_batch_download,
+            commands::generated_images::generated_images_regenerate,
+            // collections
+            commands::collections::collections_list,
+            commands::collections::collections_create,
+            commands::collections::collections_rename,
+            commands::collections::collections_delete,
+            commands::collections::collections_add_items,
+            commands::collections::collections_remove_items,
+            commands::collections::collections_get_items,
+            commands::collections::collections_export,
+            // recycle bin
+            commands::trash::trash_list,
+            commands::trash::trash_restore,
+            commands::trash::trash_purge,
+            commands::trash::trash_empty,
+            commands::trash::trash_get_policy,
+            commands::trash::trash_set_policy,
             // perfect pixel
             commands::perfect_pixel::perfect_pixel_select_image,
             commands::perfect_pixel::perfect_pixel_read_image,
             commands::perfect_pixel::perfect_pixel_save,
         ])
-        .register_asynchronous_uri_scheme_protocol("media", |_ctx, request, responder| {
+        .register_asynchronous_uri_scheme_protocol("media", |ctx, request, responder| {
             // Custom protocol handler for loading local media files
+            let outputs_folder = crate::state::get_outputs_folder(&ctx.app_handle().state::<AppState>());
             std::thread::spawn(move || {
                 let uri = request.uri().to_string();
                 let file_path = uri.strip_prefix("media://").unwrap_or("");
+                // `?size=256[&format=webp]` requests a cached thumbnail instead of the file
+                let (file_path, query) = file_path.split_once('?').unwrap_or((file_path, ""));
+                let query_param = |key: &str| {
+                    query
+                        .split('&')
+                        .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+                };
                 let file_path = urlencoding::decode(file_path)
                     .unwrap_or_else(|_| std::borrow::Cow::Borrowed(file_path));
 
@@ -104,10 +165,33 @@ pub fn run() {
                     }
                 }
 
+                if let Some(size) = query_param("size").and_then(|s| s.parse::<u32>().ok()) {
+                    let format = query_param("format").unwrap_or("jpeg");
+                    match commands::thumbnails::get_or_create_thumbnail(
+                        &outputs_folder,
+                        std::path::Path::new(&path),
+                        size,
+                        format,
+                    ) {
+                        Ok(thumb_path) => path = thumb_path.to_string_lossy().to_string(),
+                        Err(e) => {
+                            responder.respond(
+                                tauri::http::Response::builder()
+                                    .status(404)
+                                    .body(format!("Thumbnail unavailable: {}", e).into_bytes())
+                                    .unwrap(),
+                            );
+                            return;
+                        }
+                    }
+                }
+
                 match std::fs::read(&path) {
                     Ok(data) => {
                         let mime = if path.ends_with(".mp4") {
                             "video/mp4"
+                        } else if path.ends_with(".webm") {
+                            "video/webm"
                         } else if path.ends_with(".avi") {
                             "video/avi"
                         } else if path.ends_with(".png") {
@@ -155,6 +239,9 @@ pub fn run() {
             *state.sdcpp_folder.lock().unwrap() = Some(sdcpp_folder.to_string_lossy().to_string());
             *state.outputs_folder.lock().unwrap() = Some(outputs_folder.to_string_lossy().to_string());
 
+            // Report files added or removed outside the app
+            watcher::start(app.handle().clone());
+
             Ok(())
         })
         .run(tauri::generate_context!())
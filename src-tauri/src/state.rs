@@ -1,3 +1,5 @@
+use crate::commands::gallery_index::GalleryIndex;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Mutex;
 use tokio::sync::watch;
@@ -20,16 +22,77 @@ impl Default for DownloadConfig {
     }
 }
 
+pub const DEFAULT_TRASH_MAX_AGE_DAYS: u32 = 30;
+pub const DEFAULT_TRASH_MAX_SIZE_MB: u64 = 50 * 1024;
+
+/// Automatic purge policy for the recycle bin; 0 disables a limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashPolicy {
+    /// Entries deleted longer ago than this are purged
+    pub max_age_days: u32,
+    /// Oldest entries are purged while the bin is larger than this
+    pub max_size_mb: u64,
+}
+
+impl Default for TrashPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: DEFAULT_TRASH_MAX_AGE_DAYS,
+            max_size_mb: DEFAULT_TRASH_MAX_SIZE_MB,
+        }
+    }
+}
+
+/// An extra, read-only folder shown in the gallery next to the outputs folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryRoot {
+    pub path: String,
+    /// Display name for the UI
+    pub label: Option<String>,
+    /// Subfolder levels to scan; 0 scans only the folder itself
+    #[serde(default)]
+    pub depth: u32,
+}
+
+/// Holds a command's cancel sender in its `AppState` slot and clears the slot when dropped,
+/// so every exit path (including `?` errors) resets it
+pub struct CancelGuard<'a>(&'a Mutex<Option<watch::Sender<bool>>>);
+
+impl<'a> CancelGuard<'a> {
+    pub fn install(slot: &'a Mutex<Option<watch::Sender<bool>>>, sender: watch::Sender<bool>) -> Self {
+        *slot.lock().unwrap() = Some(sender);
+        Self(slot)
+    }
+}
+
+impl Drop for CancelGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
 pub struct AppState {
     pub weights_folder: Mutex<Option<String>>,
     pub sdcpp_folder: Mutex<Option<String>>,
     pub sdcpp_device_type: Mutex<String>,
     pub outputs_folder: Mutex<Option<String>>,
+    pub gallery_roots: Mutex<Vec<GalleryRoot>>,
+    pub gallery_index: tokio::sync::Mutex<Option<GalleryIndex>>,
+    /// Serializes read-modify-write of the collections store
+    pub collections_lock: Mutex<()>,
+    pub embeddings_folder: Mutex<Option<String>>,
     pub generate_cancel: Mutex<Option<watch::Sender<bool>>>,
     pub video_generate_cancel: Mutex<Option<watch::Sender<bool>>>,
     pub download_cancel: Mutex<Option<watch::Sender<bool>>>,
+    pub convert_cancel: Mutex<Option<watch::Sender<bool>>>,
+    pub ffmpeg_download_cancel: Mutex<Option<watch::Sender<bool>>>,
+    pub postprocess_cancel: Mutex<Option<watch::Sender<bool>>>,
+    pub ffmpeg_path: Mutex<Option<String>>,
     pub hf_mirror_id: Mutex<String>,
     pub download_config: Mutex<DownloadConfig>,
+    pub trash_policy: Mutex<TrashPolicy>,
 }
 
 impl AppState {
@@ -39,11 +102,20 @@ impl AppState {
             sdcpp_folder: Mutex::new(None),
             sdcpp_device_type: Mutex::new("cpu".to_string()),
             outputs_folder: Mutex::new(None),
+            gallery_roots: Mutex::new(Vec::new()),
+            gallery_index: tokio::sync::Mutex::new(None),
+            collections_lock: Mutex::new(()),
+            embeddings_folder: Mutex::new(None),
             generate_cancel: Mutex::new(None),
             video_generate_cancel: Mutex::new(None),
             download_cancel: Mutex::new(None),
+            convert_cancel: Mutex::new(None),
+            ffmpeg_download_cancel: Mutex::new(None),
+            postprocess_cancel: Mutex::new(None),
+            ffmpeg_path: Mutex::new(None),
             hf_mirror_id: Mutex::new("huggingface".to_string()),
             download_config: Mutex::new(DownloadConfig::default()),
+            trash_policy: Mutex::new(TrashPolicy::default()),
         }
     }
 }
@@ -83,7 +155,19 @@ pub fn get_default_outputs_folder() -> PathBuf {
     get_run_path().join("outputs")
 }
 
-/// Get the FFmpeg executable path
+/// Get the configured outputs folder, falling back to the default
+pub fn get_outputs_folder(state: &AppState) -> PathBuf {
+    state
+        .outputs_folder
+        .lock()
+        .unwrap()
+        .clone()
+        .filter(|folder| !folder.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(get_default_outputs_folder)
+}
+
+/// Get the bundled FFmpeg executable path (engines/ffmpeg/bin)
 pub fn get_ffmpeg_path() -> PathBuf {
     get_run_path().join("engines").join("ffmpeg").join("bin").join(
         if cfg!(target_os = "windows") { "ffmpeg.exe" } else { "ffmpeg" }
@@ -0,0 +1,282 @@
+use crate::commands::gallery_index::{apply_gallery_changes, rescan_gallery_changes, GalleryChangedEvent};
+use crate::commands::generated_images::{gallery_location, GALLERY_EXTENSIONS};
+use crate::commands::trash::TRASH_DIR_NAME;
+use crate::commands::weights::WeightFile;
+use crate::state::{self, AppState, GalleryRoot};
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, DebouncedEvent};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Filesystem events are batched until the watched folders have been quiet for this long
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// How often the configured folders are compared with the watched ones. This only reads the
+/// settings; nothing on disk is scanned
+const SETTINGS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Payload of `weights:changed`
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightsChangedEvent {
+    pub folder: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    /// Listing entries of the added and modified files
+    pub files: Vec<WeightFile>,
+}
+
+/// The folders being watched, as configured in the settings
+#[derive(PartialEq)]
+struct WatchedFolders {
+    outputs_folder: PathBuf,
+    roots: Vec<GalleryRoot>,
+    weights_folder: PathBuf,
+}
+
+impl WatchedFolders {
+    fn current(state: &AppState) -> Self {
+        Self {
+            outputs_folder: state::get_outputs_folder(state),
+            roots: state.gallery_roots.lock().unwrap().clone(),
+            weights_folder: state::get_active_models_folder(state.weights_folder.lock().unwrap().as_deref()),
+        }
+    }
+
+    /// Each folder with how it is watched. The outputs folder and depth-0 roots list only
+    /// their own files, the weights folder is listed recursively
+    fn watch_list(&self) -> Vec<(PathBuf, RecursiveMode)> {
+        let mut list = vec![(self.outputs_folder.clone(), RecursiveMode::NonRecursive)];
+        for root in &self.roots {
+            let mode = if root.depth > 0 {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            list.push((PathBuf::from(&root.path), mode));
+        }
+        list.push((self.weights_folder.clone(), RecursiveMode::Recursive));
+        list
+    }
+}
+
+/// What happened to a path within one batch
+#[derive(Clone, Copy, PartialEq)]
+enum Change {
+    Created,
+    Removed,
+    Modified,
+}
+
+/// The paths of an event with what happened to each. A rename seen from both ends removes
+/// the old path and creates the new one
+fn event_changes(event: &DebouncedEvent) -> Vec<(PathBuf, Change)> {
+    let paths = &event.event.paths;
+    let change = match event.event.kind {
+        EventKind::Access(_) => return Vec::new(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+            return vec![(paths[0].clone(), Change::Removed), (paths[1].clone(), Change::Created)];
+        }
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => Change::Created,
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => Change::Removed,
+        _ => Change::Modified,
+    };
+    paths.iter().map(|path| (path.clone(), change)).collect()
+}
+
+/// Gallery and weights changes of one batch, keyed by path with the first change seen
+#[derive(Default)]
+struct Batch {
+    gallery: HashMap<PathBuf, (Change, Option<String>)>,
+    /// A gallery subfolder appeared or disappeared, so its files have to be found by a rescan
+    gallery_rescan: bool,
+    weights: HashMap<PathBuf, Change>,
+}
+
+impl Batch {
+    fn collect(folders: &WatchedFolders, events: &[DebouncedEvent]) -> Self {
+        let location = |path: &Path| gallery_location(path, &folders.outputs_folder, &folders.roots);
+
+        let mut batch = Self::default();
+        for (path, change) in events.iter().flat_map(event_changes) {
+            if path.starts_with(&folders.weights_folder) {
+                let in_trash = path
+                    .strip_prefix(&folders.weights_folder)
+                    .is_ok_and(|rel| rel.components().any(|c| c.as_os_str() == TRASH_DIR_NAME));
+                if !in_trash {
+                    batch.weights.entry(path.clone()).or_insert(change);
+                }
+            }
+
+            if let Some(root) = location(&path) {
+                batch.gallery.entry(path).or_insert((change, root));
+            } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+                // A sidecar edit (tags, notes) changes the gallery info of its media file
+                for ext in GALLERY_EXTENSIONS {
+                    let media = path.with_extension(ext);
+                    if let Some(root) = location(&media).filter(|_| media.is_file()) {
+                        batch.gallery.entry(media).or_insert((Change::Modified, root));
+                    }
+                }
+            } else if path.is_dir() || (change == Change::Removed && path.extension().is_none()) {
+                // Folders count when files directly inside them would be listed
+                batch.gallery_rescan |= location(&path.join("_.png")).is_some();
+            }
+        }
+        batch
+    }
+}
+
+/// Sort a batch into added, removed and modified paths by the first change seen and whether
+/// the path exists now. Paths created and deleted again within the batch are dropped
+fn classify<T>(changes: HashMap<PathBuf, T>, change_of: impl Fn(&T) -> Change) -> [Vec<(PathBuf, T)>; 3] {
+    let (mut added, mut removed, mut modified) = (Vec::new(), Vec::new(), Vec::new());
+    for (path, value) in changes {
+        let change = change_of(&value);
+        match (path.exists(), change) {
+            (true, Change::Created) => added.push((path, value)),
+            (true, _) => modified.push((path, value)),
+            (false, Change::Created) => {}
+            (false, _) => removed.push((path, value)),
+        }
+    }
+    [added, removed, modified]
+}
+
+fn to_strings<T>(paths: &[(PathBuf, T)]) -> Vec<String> {
+    paths.iter().map(|(p, _)| p.to_string_lossy().to_string()).collect()
+}
+
+async fn report_gallery(app: &AppHandle, state: &AppState, batch: Batch) {
+    let event = if batch.gallery_rescan {
+        match rescan_gallery_changes(state).await {
+            Ok(event) => event,
+            Err(_) => return,
+        }
+    } else {
+        let [added, removed, modified] = classify(batch.gallery, |(change, _)| *change);
+        let changed = added
+            .iter()
+            .chain(&modified)
+            .map(|(path, (_, root))| (path.clone(), root.clone()))
+            .collect();
+        let removed_paths: Vec<PathBuf> = removed.iter().map(|(path, _)| path.clone()).collect();
+        let items = apply_gallery_changes(state, changed, &removed_paths).await;
+        GalleryChangedEvent {
+            added: to_strings(&added),
+            removed: to_strings(&removed),
+            modified: to_strings(&modified),
+            items,
+        }
+    };
+    if !(event.added.is_empty() && event.removed.is_empty() && event.modified.is_empty()) {
+        let _ = app.emit("gallery:changed", event);
+    }
+}
+
+fn report_weights(app: &AppHandle, weights_folder: &Path, changes: HashMap<PathBuf, Change>) {
+    let [added, removed, modified] = classify(changes, |change| *change);
+    if added.is_empty() && removed.is_empty() && modified.is_empty() {
+        return;
+    }
+    let files = added
+        .iter()
+        .chain(&modified)
+        .filter_map(|(path, _)| {
+            let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+            Some(WeightFile {
+                name: path
+                    .strip_prefix(weights_folder)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .to_string(),
+                size: metadata.len(),
+                path: path.to_string_lossy().to_string(),
+                modified: metadata
+                    .modified()
+                    .map(|t| {
+                        t.duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_millis() as u64
+                    })
+                    .unwrap_or(0),
+                cpu_variant: None,
+            })
+        })
+        .collect();
+    let _ = app.emit(
+        "weights:changed",
+        WeightsChangedEvent {
+            folder: weights_folder.to_string_lossy().to_string(),
+            added: to_strings(&added),
+            removed: to_strings(&removed),
+            modified: to_strings(&modified),
+            files,
+        },
+    );
+}
+
+/// Watch the outputs folder (with the extra gallery roots) and the weights folder for
+/// filesystem events, emitting `gallery:changed` and `weights:changed` when files change
+/// outside the app's own commands too. Gallery changes are also applied to the gallery index
+pub fn start(app: AppHandle) {
+    let (events_tx, mut events_rx) = tokio::sync::mpsc::unbounded_channel();
+    let debouncer = new_debouncer(DEBOUNCE_TIMEOUT, None, move |result: DebounceEventResult| {
+        // Watcher errors (e.g. a watched folder removed) are recovered by the settings check
+        if let Ok(events) = result {
+            let _ = events_tx.send(events);
+        }
+    });
+    let Ok(mut debouncer) = debouncer else {
+        return;
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let mut folders: Option<WatchedFolders> = None;
+        let mut watched: Vec<PathBuf> = Vec::new();
+        let mut settings_check = tokio::time::interval(SETTINGS_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = settings_check.tick() => {
+                    let state = app.state::<AppState>();
+                    let current = WatchedFolders::current(&state);
+                    // Folders that didn't exist yet are picked up once they do, and deleted
+                    // ones are dropped
+                    let stale = current
+                        .watch_list()
+                        .iter()
+                        .any(|(path, _)| watched.contains(path) != path.is_dir());
+                    if folders.as_ref() == Some(&current) && !stale {
+                        continue;
+                    }
+                    for path in watched.drain(..) {
+                        let _ = debouncer.unwatch(&path);
+                    }
+                    for (path, mode) in current.watch_list() {
+                        if debouncer.watch(&path, mode).is_ok() {
+                            watched.push(path);
+                        }
+                    }
+                    folders = Some(current);
+                }
+                Some(events) = events_rx.recv() => {
+                    let Some(folders) = &folders else {
+                        continue;
+                    };
+                    let state = app.state::<AppState>();
+                    let mut batch = Batch::collect(folders, &events);
+                    let weights = std::mem::take(&mut batch.weights);
+                    report_weights(&app, &folders.weights_folder, weights);
+                    if batch.gallery_rescan || !batch.gallery.is_empty() {
+                        report_gallery(&app, &state, batch).await;
+                    }
+                }
+            }
+        }
+    });
+}
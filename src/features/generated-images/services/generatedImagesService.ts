@@ -1,6 +1,10 @@
 import { ipcInvoke, ipcInvokeWithPayload } from '@/lib/tauriIpc'
+import type { GalleryAnnotationFilter } from '@shared/types'
 
-async function listGeneratedImages() {
+async function listGeneratedImages(filter?: GalleryAnnotationFilter) {
+  if (filter) {
+    return ipcInvokeWithPayload('generated-images:list', { value: filter })
+  }
   return ipcInvoke('generated-images:list')
 }
 
@@ -34,6 +34,7 @@ import {
   ListRegular,
 } from '@/ui/icons'
 import { generatedImagesService } from '@/features/generated-images/services/generatedImagesService'
+import { useIpcListener } from '../hooks/useIpcListener'
 import { PhotoView } from 'react-photo-view'
 import { useState, useEffect, useMemo, useCallback } from 'react'
 import type { GeneratedImage } from '../../shared/types'
@@ -383,6 +384,29 @@ export const GeneratedImagesPage = () => {
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [])
 
+  // 监听图库目录变化（包括应用外部的增删改），增量更新列表
+  useIpcListener('gallery:changed', (data) => {
+    const removed = new Set(data.removed)
+    const changed = new Map(data.items.map((item) => [item.path, item]))
+    setImages((prev) => {
+      const kept = prev.filter((img) => !removed.has(img.path) && !changed.has(img.path))
+      // 与后端列表保持一致：按修改时间倒序
+      return [...kept, ...changed.values()].sort((a, b) => b.modified - a.modified)
+    })
+    if (removed.size > 0) {
+      setSelectedImages((prev) => new Set([...prev].filter((path) => !removed.has(path))))
+      setLoadedPreviews((prev) => new Map([...prev].filter(([path]) => !removed.has(path))))
+    }
+    // 新增或被修改的图片重新加载预览图
+    for (const item of data.items) {
+      if (item.mediaType === 'video') continue
+      generatedImagesService
+        .getGeneratedImagePreview(item.path)
+        .then((base64) => setLoadedPreviews((prev) => new Map(prev).set(item.path, base64)))
+        .catch(console.error)
+    }
+  })
+
   // 保存视图模式到 localStorage
   useEffect(() => {
     localStorage.setItem('generated-images-view-mode', viewMode)
@@ -1253,7 +1277,7 @@ export const GeneratedImagesPage = () => {
           <DialogTitle>确认删除</DialogTitle>
           <DialogBody>
             <DialogContent>
-              <Body1>确定要删除这张图片吗？此操作无法撤销。</Body1>
+              <Body1>确定要删除这张图片吗？图片将移至回收站，可在回收站中恢复。</Body1>
             </DialogContent>
           </DialogBody>
           <DialogActions>
@@ -1279,7 +1303,7 @@ export const GeneratedImagesPage = () => {
           <DialogTitle>确认批量删除</DialogTitle>
           <DialogBody>
             <DialogContent>
-              <Body1>确定要删除选中的 {selectedImages.size} 张图片吗？此操作无法撤销。</Body1>
+              <Body1>确定要删除选中的 {selectedImages.size} 张图片吗？图片将移至回收站，可在回收站中恢复。</Body1>
             </DialogContent>
           </DialogBody>
           <DialogActions>
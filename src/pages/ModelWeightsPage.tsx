@@ -477,6 +477,24 @@ export const ModelWeightsPage = () => {
     loadModelGroups().catch(console.error)
   }, [])
 
+  // 监听权重目录变化（包括应用外部的增删改），只重新检查受影响的模型组
+  useIpcListener('weights:changed', (data) => {
+    const changedPaths = [...data.added, ...data.removed, ...data.modified]
+    const relativePaths = changedPaths.map((path) => path.slice(data.folder.length).replace(/^[\\/]+/, ''))
+    if (relativePaths.includes('model-groups.json')) {
+      loadModelGroups().catch(console.error)
+      return
+    }
+    const changedFolders = new Set(relativePaths.map((path) => path.split(/[\\/]/)[0]))
+    for (const group of modelGroups) {
+      if (!group.hfFiles?.length || !changedFolders.has(group.folder || group.name)) continue
+      modelWeightsService
+        .checkModelGroupFiles(group.id)
+        .then((result) => setFileStatusMap((prev) => ({ ...prev, [group.id]: result })))
+        .catch(console.error)
+    }
+  })
+
   const loadWeightsFolder = async () => {
     let folder = await modelWeightsService.getWeightsFolder()
 